@@ -0,0 +1,771 @@
+// Copyright 2018 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Planar geometry helpers working on decoded (GeoJSON) coordinates.
+
+use crate::Position;
+use geojson::Value as GeoJsonGeomValue;
+
+pub fn positions_approx_eq(a: &[f64], b: &[f64], epsilon: f64) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(u, v)| (u - v).abs() <= epsilon)
+}
+
+/// Drop the positions equal (within `epsilon`) to their predecessor.
+fn dedup_positions(line: &[Position], epsilon: f64) -> Vec<&Position> {
+    let mut result: Vec<&Position> = Vec::with_capacity(line.len());
+    for pos in line {
+        match result.last() {
+            Some(last) if positions_approx_eq(last, pos, epsilon) => {}
+            _ => result.push(pos),
+        }
+    }
+    result
+}
+
+fn lines_approx_eq(a: &[Position], b: &[Position], epsilon: f64) -> bool {
+    let (a, b) = (dedup_positions(a, epsilon), dedup_positions(b, epsilon));
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(u, v)| positions_approx_eq(u, v, epsilon))
+}
+
+/// Compare two rings, whatever the position they start from.
+fn rings_approx_eq(a: &[Position], b: &[Position], epsilon: f64) -> bool {
+    let mut a = dedup_positions(a, epsilon);
+    let mut b = dedup_positions(b, epsilon);
+    // Drop the closing positions:
+    if a.len() > 1 && positions_approx_eq(a[0], a[a.len() - 1], epsilon) {
+        a.pop();
+    }
+    if b.len() > 1 && positions_approx_eq(b[0], b[b.len() - 1], epsilon) {
+        b.pop();
+    }
+    if a.len() != b.len() {
+        return false;
+    }
+    if a.is_empty() {
+        return true;
+    }
+    (0..b.len()).any(|offset| {
+        a.iter()
+            .zip(b.iter().cycle().skip(offset))
+            .all(|(u, v)| positions_approx_eq(u, v, epsilon))
+    })
+}
+
+fn polygons_approx_eq(a: &[Vec<Position>], b: &[Vec<Position>], epsilon: f64) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(u, v)| rings_approx_eq(u, v, epsilon))
+}
+
+/// Compare two decoded geometries, tolerating differences of at most `epsilon` on
+/// each coordinate, repeated consecutive positions, and rings starting from a
+/// different position.
+pub fn geom_values_approx_eq(a: &GeoJsonGeomValue, b: &GeoJsonGeomValue, epsilon: f64) -> bool {
+    match (a, b) {
+        (GeoJsonGeomValue::Point(u), GeoJsonGeomValue::Point(v)) => {
+            positions_approx_eq(u, v, epsilon)
+        }
+        (GeoJsonGeomValue::MultiPoint(u), GeoJsonGeomValue::MultiPoint(v)) => {
+            u.len() == v.len()
+                && u.iter()
+                    .zip(v)
+                    .all(|(p, q)| positions_approx_eq(p, q, epsilon))
+        }
+        (GeoJsonGeomValue::LineString(u), GeoJsonGeomValue::LineString(v)) => {
+            lines_approx_eq(u, v, epsilon)
+        }
+        (GeoJsonGeomValue::MultiLineString(u), GeoJsonGeomValue::MultiLineString(v)) => {
+            u.len() == v.len() && u.iter().zip(v).all(|(p, q)| lines_approx_eq(p, q, epsilon))
+        }
+        (GeoJsonGeomValue::Polygon(u), GeoJsonGeomValue::Polygon(v)) => {
+            polygons_approx_eq(u, v, epsilon)
+        }
+        (GeoJsonGeomValue::MultiPolygon(u), GeoJsonGeomValue::MultiPolygon(v)) => {
+            u.len() == v.len()
+                && u.iter()
+                    .zip(v)
+                    .all(|(p, q)| polygons_approx_eq(p, q, epsilon))
+        }
+        (GeoJsonGeomValue::GeometryCollection(u), GeoJsonGeomValue::GeometryCollection(v)) => {
+            u.len() == v.len()
+                && u.iter()
+                    .zip(v)
+                    .all(|(p, q)| geom_values_approx_eq(&p.value, &q.value, epsilon))
+        }
+        _ => false,
+    }
+}
+
+/// Extend `bbox` (`[minx, miny, maxx, maxy]`) to include `pos`.
+///
+/// Positions with less than two components (which `Topology::validate` reports)
+/// have no place in an extent, so they are ignored.
+pub fn extend_bbox(bbox: &mut Option<[f64; 4]>, pos: &[f64]) {
+    let (x, y) = match *pos {
+        [x, y, ..] => (x, y),
+        _ => return,
+    };
+    match bbox {
+        None => *bbox = Some([x, y, x, y]),
+        Some(b) => {
+            b[0] = b[0].min(x);
+            b[1] = b[1].min(y);
+            b[2] = b[2].max(x);
+            b[3] = b[3].max(y);
+        }
+    }
+}
+
+/// The positions of `line` with at least two components (the others are ignored,
+/// as in `extend_bbox`).
+fn planar_positions(line: &[Position]) -> Vec<&Position> {
+    line.iter().filter(|pos| pos.len() >= 2).collect()
+}
+
+/// Call `f` on every position of a GeoJSON geometry value
+/// (descending into geometry collections).
+pub fn for_each_geojson_position<F: FnMut(&Position)>(value: &GeoJsonGeomValue, f: &mut F) {
+    match value {
+        GeoJsonGeomValue::Point(pos) => f(pos),
+        GeoJsonGeomValue::MultiPoint(line) | GeoJsonGeomValue::LineString(line) => {
+            line.iter().for_each(f)
+        }
+        GeoJsonGeomValue::MultiLineString(lines) | GeoJsonGeomValue::Polygon(lines) => {
+            lines.iter().flatten().for_each(f)
+        }
+        GeoJsonGeomValue::MultiPolygon(polygons) => polygons.iter().flatten().flatten().for_each(f),
+        GeoJsonGeomValue::GeometryCollection(geoms) => geoms
+            .iter()
+            .for_each(|g| for_each_geojson_position(&g.value, f)),
+    }
+}
+
+/// Call `f` on a mutable reference to every position of a GeoJSON geometry value
+/// (descending into geometry collections).
+pub fn for_each_geojson_position_mut<F: FnMut(&mut Position)>(
+    value: &mut GeoJsonGeomValue,
+    f: &mut F,
+) {
+    match value {
+        GeoJsonGeomValue::Point(pos) => f(pos),
+        GeoJsonGeomValue::MultiPoint(line) | GeoJsonGeomValue::LineString(line) => {
+            line.iter_mut().for_each(f)
+        }
+        GeoJsonGeomValue::MultiLineString(lines) | GeoJsonGeomValue::Polygon(lines) => {
+            lines.iter_mut().flatten().for_each(f)
+        }
+        GeoJsonGeomValue::MultiPolygon(polygons) => {
+            polygons.iter_mut().flatten().flatten().for_each(f)
+        }
+        GeoJsonGeomValue::GeometryCollection(geoms) => geoms
+            .iter_mut()
+            .for_each(|g| for_each_geojson_position_mut(&mut g.value, f)),
+    }
+}
+
+/// Extent (`[minx, miny, maxx, maxy]`) of a GeoJSON geometry value,
+/// `None` if it doesn't contain any position.
+pub fn geom_value_extent(value: &GeoJsonGeomValue) -> Option<[f64; 4]> {
+    let mut extent = None;
+    for_each_geojson_position(value, &mut |pos| extend_bbox(&mut extent, pos));
+    extent
+}
+
+/// Whether two `[minx, miny, maxx, maxy]` extents intersect (touching counts).
+pub fn bboxes_intersect(a: &[f64; 4], b: &[f64; 4]) -> bool {
+    a[0] <= b[2] && b[0] <= a[2] && a[1] <= b[3] && b[1] <= a[3]
+}
+
+/// Centroid of the positions (vertices) of a GeoJSON geometry value,
+/// `None` if it doesn't contain any position.
+pub fn geom_value_centroid(value: &GeoJsonGeomValue) -> Option<[f64; 2]> {
+    let (mut x, mut y, mut n) = (0., 0., 0usize);
+    for_each_geojson_position(value, &mut |pos| {
+        if pos.len() >= 2 {
+            x += pos[0];
+            y += pos[1];
+            n += 1;
+        }
+    });
+    if n == 0 {
+        None
+    } else {
+        Some([x / n as f64, y / n as f64])
+    }
+}
+
+/// Morton (Z-order) code of a cell: the bits of `x` and `y` interleaved,
+/// `x` giving the even bits.
+pub fn morton_code(x: u16, y: u16) -> u32 {
+    fn spread(v: u16) -> u32 {
+        let mut v = v as u32;
+        v = (v | (v << 8)) & 0x00FF_00FF;
+        v = (v | (v << 4)) & 0x0F0F_0F0F;
+        v = (v | (v << 2)) & 0x3333_3333;
+        (v | (v << 1)) & 0x5555_5555
+    }
+    spread(x) | (spread(y) << 1)
+}
+
+/// Orientation of the triangle (a, b, c): positive if counter-clockwise,
+/// negative if clockwise and zero if the points are collinear.
+fn orientation(a: &[f64], b: &[f64], c: &[f64]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Whether `c`, known to be collinear with `a` and `b`, lies on the segment (a, b).
+fn on_segment(a: &[f64], b: &[f64], c: &[f64]) -> bool {
+    c[0] >= a[0].min(b[0])
+        && c[0] <= a[0].max(b[0])
+        && c[1] >= a[1].min(b[1])
+        && c[1] <= a[1].max(b[1])
+}
+
+pub fn segments_intersect(a: &[f64], b: &[f64], c: &[f64], d: &[f64]) -> bool {
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    if ((o1 > 0. && o2 < 0.) || (o1 < 0. && o2 > 0.))
+        && ((o3 > 0. && o4 < 0.) || (o3 < 0. && o4 > 0.))
+    {
+        return true;
+    }
+    (o1 == 0. && on_segment(a, b, c))
+        || (o2 == 0. && on_segment(a, b, d))
+        || (o3 == 0. && on_segment(c, d, a))
+        || (o4 == 0. && on_segment(c, d, b))
+}
+
+/// Signed area of a ring (positive if counter-clockwise, negative if clockwise).
+pub fn ring_area(ring: &[Position]) -> f64 {
+    let ring = planar_positions(ring);
+    let n = ring.len();
+    if n < 3 {
+        return 0.;
+    }
+    (0..n)
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum::<f64>()
+        / 2.
+}
+
+/// Length of a ring, including the segment closing it if its endpoints differ.
+pub fn ring_perimeter(ring: &[Position]) -> f64 {
+    let ring = planar_positions(ring);
+    let n = ring.len();
+    if n < 2 {
+        return 0.;
+    }
+    (0..n)
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % n]);
+            (b[0] - a[0]).hypot(b[1] - a[1])
+        })
+        .sum()
+}
+
+/// Whether `pt` is inside `ring` (using the even-odd rule),
+/// `None` if it lies on the boundary of the ring.
+fn point_in_ring(pt: &[f64], ring: &[Position]) -> Option<bool> {
+    if pt.len() < 2 {
+        return None;
+    }
+    let ring = planar_positions(ring);
+    let n = ring.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (ring[i], ring[(i + 1) % n]);
+        if orientation(a, b, pt) == 0. && on_segment(a, b, pt) {
+            return None;
+        }
+        if (a[1] > pt[1]) != (b[1] > pt[1])
+            && pt[0] < a[0] + (pt[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0])
+        {
+            inside = !inside;
+        }
+    }
+    Some(inside)
+}
+
+/// Whether the ring `inner` lies inside the ring `outer`, tested on the first
+/// position of `inner` which isn't on the boundary of `outer` (rings which
+/// share all their positions are not inside each other).
+fn ring_inside(inner: &[Position], outer: &[Position]) -> bool {
+    inner
+        .iter()
+        .find_map(|pt| point_in_ring(pt, outer))
+        .unwrap_or(false)
+}
+
+/// Group rings into polygons by containment, whatever their winding order.
+///
+/// A ring is an exterior ring when it is contained in an even number of the other
+/// rings, and a hole otherwise. Each hole is given to the smallest exterior
+/// ring containing it. Polygons follow the order of their exterior ring
+/// and holes keep their relative order.
+pub fn group_rings(rings: Vec<Vec<Position>>) -> Vec<Vec<Vec<Position>>> {
+    let n = rings.len();
+    let containers: Vec<Vec<usize>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .filter(|&j| j != i && ring_inside(&rings[i], &rings[j]))
+                .collect()
+        })
+        .collect();
+    let is_exterior: Vec<bool> = containers.iter().map(|c| c.len() % 2 == 0).collect();
+    let parents: Vec<Option<usize>> = (0..n)
+        .map(|i| {
+            if is_exterior[i] {
+                return None;
+            }
+            containers[i]
+                .iter()
+                .copied()
+                .filter(|&j| is_exterior[j])
+                .min_by(|&u, &v| {
+                    ring_area(&rings[u])
+                        .abs()
+                        .total_cmp(&ring_area(&rings[v]).abs())
+                })
+        })
+        .collect();
+
+    let mut polygons: Vec<Vec<Vec<Position>>> = Vec::new();
+    let mut polygon_of = vec![0; n];
+    for (i, ring) in rings.iter().enumerate() {
+        if parents[i].is_none() {
+            polygon_of[i] = polygons.len();
+            polygons.push(vec![ring.clone()]);
+        }
+    }
+    for (i, ring) in rings.into_iter().enumerate() {
+        if let Some(parent) = parents[i] {
+            polygons[polygon_of[parent]].push(ring);
+        }
+    }
+    polygons
+}
+
+/// Give each hole of a multipolygon to the smallest exterior ring (the first ring of
+/// each polygon) containing it. Holes not contained in any exterior ring are left
+/// in their polygon.
+pub fn reassign_holes(polygons: Vec<Vec<Vec<Position>>>) -> Vec<Vec<Vec<Position>>> {
+    let exteriors: Vec<Vec<Position>> = polygons
+        .iter()
+        .filter_map(|rings| rings.first().cloned())
+        .collect();
+    let mut result: Vec<Vec<Vec<Position>>> = exteriors.iter().map(|r| vec![r.clone()]).collect();
+    let mut exterior_ix = 0;
+    for rings in polygons.into_iter() {
+        if rings.is_empty() {
+            continue;
+        }
+        for hole in rings.into_iter().skip(1) {
+            let target = (0..exteriors.len())
+                .filter(|&i| ring_inside(&hole, &exteriors[i]))
+                .min_by(|&u, &v| {
+                    ring_area(&exteriors[u])
+                        .abs()
+                        .total_cmp(&ring_area(&exteriors[v]).abs())
+                })
+                .unwrap_or(exterior_ix);
+            result[target].push(hole);
+        }
+        exterior_ix += 1;
+    }
+    result
+}
+
+/// Whether a ring intersects itself (consecutive segments sharing their
+/// common endpoint don't count as an intersection).
+///
+/// Segments are sorted by their minimum x coordinate and each segment is only tested
+/// against the following segments whose x range overlaps its own.
+pub fn ring_self_intersects(ring: &[Position]) -> bool {
+    let mut points = dedup_positions(ring, 0.);
+    points.retain(|pos| pos.len() >= 2);
+    if points.len() > 1 && points[0] == points[points.len() - 1] {
+        points.pop();
+    }
+    let n = points.len();
+    if n < 3 {
+        return false;
+    }
+    // Segment i goes from points[i] to points[(i + 1) % n]:
+    let mut segments: Vec<(usize, f64, f64)> = (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            (i, a[0].min(b[0]), a[0].max(b[0]))
+        })
+        .collect();
+    segments.sort_by(|u, v| u.1.total_cmp(&v.1));
+    for (k, &(i, _, max_x)) in segments.iter().enumerate() {
+        for &(j, min_x, _) in segments[k + 1..].iter() {
+            if min_x > max_x {
+                break;
+            }
+            let adjacent = (i + 1) % n == j || (j + 1) % n == i;
+            if adjacent {
+                // Adjacent segments only intersect if they overlap beyond their common endpoint:
+                if n > 3 {
+                    let (first, second) = if (i + 1) % n == j { (i, j) } else { (j, i) };
+                    let (a, b, c) = (points[first], points[second], points[(second + 1) % n]);
+                    if orientation(a, b, c) == 0. && on_segment(a, b, c) {
+                        return true;
+                    }
+                }
+                continue;
+            }
+            if segments_intersect(
+                points[i],
+                points[(i + 1) % n],
+                points[j],
+                points[(j + 1) % n],
+            ) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Convex hull of `points` (Andrew's monotone chain), as a counter-clockwise
+/// list of vertices without repeating the first one. Collinear points are left out,
+/// so the hull of less than 3 non-collinear points has less than 3 vertices.
+pub fn convex_hull(mut points: Vec<Position>) -> Vec<Position> {
+    points.retain(|p| p.len() >= 2);
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup_by(|a, b| a[0] == b[0] && a[1] == b[1]);
+    if points.len() < 3 {
+        return points;
+    }
+    // Append one half of the hull (the lower one when the points are sorted
+    // in increasing order) to `hull`:
+    fn extend_hull<'a, I: Iterator<Item = &'a Position>>(hull: &mut Vec<Position>, points: I) {
+        let start = hull.len();
+        for p in points {
+            while hull.len() >= start + 2
+                && orientation(&hull[hull.len() - 2], &hull[hull.len() - 1], p) <= 0.
+            {
+                hull.pop();
+            }
+            hull.push(p.clone());
+        }
+        // The last point of each half is the first one of the other:
+        hull.pop();
+    }
+    let mut hull = Vec::with_capacity(points.len() + 1);
+    extend_hull(&mut hull, points.iter());
+    extend_hull(&mut hull, points.iter().rev());
+    hull
+}
+
+/// Position at `t` on the segment (a, b), interpolating all the components.
+fn interpolate(a: &[f64], b: &[f64], t: f64) -> Position {
+    a.iter()
+        .zip(b.iter())
+        .map(|(u, v)| u + t * (v - u))
+        .collect()
+}
+
+/// Clip the segment (a, b) to the rectangle `rect` (`[minx, miny, maxx, maxy]`)
+/// with the Liang–Barsky algorithm, `None` if it is entirely outside.
+fn clip_segment(a: &[f64], b: &[f64], rect: &[f64; 4]) -> Option<(Position, Position)> {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let (mut t0, mut t1) = (0f64, 1f64);
+    for (p, q) in [
+        (-dx, a[0] - rect[0]),
+        (dx, rect[2] - a[0]),
+        (-dy, a[1] - rect[1]),
+        (dy, rect[3] - a[1]),
+    ] {
+        if p == 0. {
+            if q < 0. {
+                return None;
+            }
+        } else {
+            let r = q / p;
+            if p < 0. {
+                t0 = t0.max(r);
+            } else {
+                t1 = t1.min(r);
+            }
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+    let start = if t0 > 0. {
+        interpolate(a, b, t0)
+    } else {
+        a.to_vec()
+    };
+    let end = if t1 < 1. {
+        interpolate(a, b, t1)
+    } else {
+        b.to_vec()
+    };
+    Some((start, end))
+}
+
+/// Clip a line to the rectangle `rect` (`[minx, miny, maxx, maxy]`), returning
+/// the parts of the line inside of it (a line leaving and entering the rectangle
+/// again gives several parts).
+pub fn clip_line(line: &[Position], rect: &[f64; 4]) -> Vec<Vec<Position>> {
+    let mut parts = Vec::new();
+    let mut current: Vec<Position> = Vec::new();
+    for segment in planar_positions(line).windows(2) {
+        // A part ends when the next clipped segment doesn't start where it stops
+        // (segments reduced to a point, touching a corner, are skipped):
+        if let Some((start, end)) = clip_segment(segment[0], segment[1], rect) {
+            if start == end {
+                continue;
+            }
+            if current.last() != Some(&start) {
+                if current.len() >= 2 {
+                    parts.push(std::mem::take(&mut current));
+                }
+                current = vec![start];
+            }
+            current.push(end);
+        }
+    }
+    if current.len() >= 2 {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Clip a (closed) ring to the rectangle `rect` (`[minx, miny, maxx, maxy]`)
+/// with the Sutherland–Hodgman algorithm.
+///
+/// Returns an empty ring if nothing is left of it. Like any Sutherland–Hodgman
+/// clipping, a concave ring crossing the rectangle several times gives a single
+/// ring connected by edges along the rectangle.
+pub fn clip_ring(ring: &[Position], rect: &[f64; 4]) -> Vec<Position> {
+    let mut points: Vec<Position> = match ring.split_last() {
+        Some((last, rest)) if !rest.is_empty() && rest[0] == *last => rest.to_vec(),
+        _ => ring.to_vec(),
+    };
+    points.retain(|pos| pos.len() >= 2);
+    // The four half-planes bounding the rectangle, as (axis, bound, keep lower side):
+    for (axis, bound, lower) in [
+        (0, rect[0], false),
+        (0, rect[2], true),
+        (1, rect[1], false),
+        (1, rect[3], true),
+    ] {
+        let inside = |p: &Position| {
+            if lower {
+                p[axis] <= bound
+            } else {
+                p[axis] >= bound
+            }
+        };
+        let input = std::mem::take(&mut points);
+        for (i, p) in input.iter().enumerate() {
+            let prev = &input[(i + input.len() - 1) % input.len()];
+            if inside(p) != inside(prev) {
+                let t = (bound - prev[axis]) / (p[axis] - prev[axis]);
+                points.push(interpolate(prev, p, t));
+            }
+            if inside(p) {
+                points.push(p.clone());
+            }
+        }
+    }
+    if points.len() < 3 {
+        return vec![];
+    }
+    points.push(points[0].clone());
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clip_line, clip_ring, convex_hull, group_rings, morton_code, reassign_holes,
+        ring_self_intersects,
+    };
+
+    #[test]
+    fn clip_line_to_rectangle() {
+        let rect = [0., 0., 2., 2.];
+        // Entering, leaving and entering the rectangle again:
+        let line = vec![
+            vec![-1., 1.],
+            vec![1., 1.],
+            vec![1., 3.],
+            vec![3., 1.],
+            vec![1., 1.],
+        ];
+        assert_eq!(
+            clip_line(&line, &rect),
+            vec![
+                vec![vec![0., 1.], vec![1., 1.], vec![1., 2.]],
+                vec![vec![2., 1.], vec![1., 1.]],
+            ]
+        );
+        assert!(clip_line(&[vec![3., 3.], vec![4., 4.]], &rect).is_empty());
+    }
+
+    #[test]
+    fn clip_ring_to_rectangle() {
+        let rect = [0., 0., 2., 2.];
+        let ring = square(1., 1., 2.);
+        assert_eq!(
+            clip_ring(&ring, &rect),
+            vec![
+                vec![1., 2.],
+                vec![1., 1.],
+                vec![2., 1.],
+                vec![2., 2.],
+                vec![1., 2.]
+            ]
+        );
+        assert!(clip_ring(&square(3., 3., 1.), &rect).is_empty());
+        assert_eq!(
+            clip_ring(&square(0.5, 0.5, 1.), &rect),
+            square(0.5, 0.5, 1.)
+        );
+    }
+
+    #[test]
+    fn convex_hull_of_points() {
+        let points = vec![
+            vec![0., 0.],
+            vec![2., 0.],
+            vec![1., 1.],
+            vec![2., 2.],
+            vec![0., 2.],
+            vec![1., 0.],
+            vec![0., 0.],
+        ];
+        assert_eq!(
+            convex_hull(points),
+            vec![vec![0., 0.], vec![2., 0.], vec![2., 2.], vec![0., 2.]]
+        );
+        let collinear = vec![vec![0., 0.], vec![2., 2.], vec![1., 1.]];
+        assert_eq!(convex_hull(collinear), vec![vec![0., 0.], vec![2., 2.]]);
+    }
+
+    #[test]
+    fn morton_codes() {
+        assert_eq!(morton_code(0, 0), 0);
+        assert_eq!(morton_code(1, 0), 1);
+        assert_eq!(morton_code(0, 1), 2);
+        assert_eq!(morton_code(3, 5), 0b100111);
+        assert_eq!(morton_code(u16::MAX, u16::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn detect_self_intersecting_rings() {
+        let square = vec![
+            vec![0., 0.],
+            vec![1., 0.],
+            vec![1., 1.],
+            vec![0., 1.],
+            vec![0., 0.],
+        ];
+        let bowtie = vec![
+            vec![0., 0.],
+            vec![2., 2.],
+            vec![2., 0.],
+            vec![0., 2.],
+            vec![0., 0.],
+        ];
+        // Repeated positions, as found at arc joins, are not an intersection:
+        let repeated = vec![
+            vec![0., 0.],
+            vec![1., 0.],
+            vec![1., 0.],
+            vec![1., 1.],
+            vec![0., 0.],
+        ];
+        // A spike going back on itself:
+        let spike = vec![
+            vec![0., 0.],
+            vec![2., 0.],
+            vec![1., 0.],
+            vec![1., 1.],
+            vec![0., 0.],
+        ];
+        assert!(!ring_self_intersects(&square));
+        assert!(ring_self_intersects(&bowtie));
+        assert!(!ring_self_intersects(&repeated));
+        assert!(ring_self_intersects(&spike));
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Vec<f64>> {
+        vec![
+            vec![x, y],
+            vec![x + size, y],
+            vec![x + size, y + size],
+            vec![x, y + size],
+            vec![x, y],
+        ]
+    }
+
+    #[test]
+    fn group_rings_by_containment() {
+        let rings = vec![
+            square(0., 0., 10.),
+            // A hole of the second exterior ring, given before it:
+            square(21., 1., 2.),
+            square(20., 0., 10.),
+            square(1., 1., 2.),
+            // An island in the hole of the first exterior ring:
+            square(1.5, 1.5, 1.),
+        ];
+        assert_eq!(
+            group_rings(rings.clone()),
+            vec![
+                vec![rings[0].clone(), rings[3].clone()],
+                vec![rings[2].clone(), rings[1].clone()],
+                vec![rings[4].clone()],
+            ]
+        );
+    }
+
+    #[test]
+    fn reassign_holes_to_containing_exterior() {
+        let (a, b) = (square(0., 0., 10.), square(20., 0., 10.));
+        let (hole_a, hole_b) = (square(1., 1., 2.), square(21., 1., 2.));
+        // `small_hole` is inside both `a` and `island` (nested in the hole of `a`),
+        // the smallest of them wins:
+        let island = square(1.2, 1.2, 1.6);
+        let small_hole = square(1.5, 1.5, 0.5);
+        let polygons = vec![
+            vec![a.clone(), hole_b.clone()],
+            vec![b.clone(), hole_a.clone()],
+            vec![island.clone(), small_hole.clone()],
+        ];
+        assert_eq!(
+            reassign_holes(polygons),
+            vec![
+                vec![a.clone(), hole_a],
+                vec![b, hole_b],
+                vec![island, small_hole],
+            ]
+        );
+
+        // Holes outside of every exterior ring are kept in their polygon:
+        let stray = square(50., 50., 1.);
+        let polygons = vec![vec![a, stray]];
+        assert_eq!(reassign_holes(polygons.clone()), polygons);
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2018 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// An arc index, as found in the 'arcs' member of a geometry.
+///
+/// Following the spec, a negative index `~i` (i.e. `-i - 1`) references the arc at
+/// position `i`, reversed. This wraps the raw `i32` (which the [`Value`](crate::Value)
+/// variants keep using) so that the sign convention is only dealt with here.
+///
+/// [TopoJSON Format Specification § 2.1.4](https://github.com/topojson/topojson-specification#214-arc-indexes)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ArcRef(i32);
+
+impl ArcRef {
+    /// The position, in the 'arcs' array of the topology, of the referenced arc.
+    ///
+    /// This uses the bitwise complement, which is defined for every i32
+    /// (`i32::MIN` gives `i32::MAX`) unlike `abs`.
+    pub fn index(&self) -> usize {
+        if self.0 < 0 {
+            !self.0 as usize
+        } else {
+            self.0 as usize
+        }
+    }
+
+    /// Whether the referenced arc is to be traversed in reverse.
+    pub fn is_reversed(&self) -> bool {
+        self.0 < 0
+    }
+
+    /// The reference to the same arc, in the other direction.
+    pub fn reversed(self) -> ArcRef {
+        ArcRef(!self.0)
+    }
+}
+
+impl From<i32> for ArcRef {
+    fn from(ix: i32) -> Self {
+        ArcRef(ix)
+    }
+}
+
+impl From<ArcRef> for i32 {
+    fn from(arc_ref: ArcRef) -> Self {
+        arc_ref.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArcRef;
+
+    #[test]
+    fn arc_ref_sign() {
+        let forward = ArcRef::from(3);
+        assert_eq!(forward.index(), 3);
+        assert!(!forward.is_reversed());
+
+        let reversed = forward.reversed();
+        assert_eq!(i32::from(reversed), -4);
+        assert_eq!(reversed.index(), 3);
+        assert!(reversed.is_reversed());
+        assert_eq!(reversed.reversed(), forward);
+
+        // The first arc, reversed, is ~0 (and not -0):
+        assert_eq!(i32::from(ArcRef::from(0).reversed()), -1);
+        assert_eq!(ArcRef::from(-1).index(), 0);
+    }
+
+    #[test]
+    fn arc_ref_extremes() {
+        for ix in [0, 1, -1, i32::MAX, i32::MIN] {
+            let arc_ref = ArcRef::from(ix);
+            assert_eq!(i32::from(arc_ref), ix);
+            assert_eq!(arc_ref.reversed().reversed(), arc_ref);
+            assert_eq!(arc_ref.reversed().index(), arc_ref.index());
+        }
+        assert_eq!(ArcRef::from(i32::MIN).index(), i32::MAX as usize);
+    }
+}
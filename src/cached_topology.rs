@@ -0,0 +1,143 @@
+// Copyright 2018 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::{Arc as SharedPtr, Mutex, OnceLock};
+
+use geojson::FeatureCollection;
+
+use crate::{to_geojson, to_geojson_in_bbox_with_arc_bboxes, Error, Topology};
+
+/// A Topology which memoizes its conversions to GeoJSON, by object name.
+///
+/// The topology can't be modified once wrapped, so the cached Feature collections
+/// never need to be invalidated. They are shared behind a `std::sync::Arc` so that serving
+/// the same object many times (possibly from several threads) doesn't copy them.
+/// The extents of the arcs are also kept, for the conversions filtered on a bbox.
+#[derive(Debug)]
+pub struct CachedTopology {
+    topology: Topology,
+    cache: Mutex<HashMap<String, SharedPtr<FeatureCollection>>>,
+    arc_bboxes: OnceLock<Vec<[f64; 4]>>,
+}
+
+impl CachedTopology {
+    pub fn new(topology: Topology) -> Self {
+        CachedTopology {
+            topology,
+            cache: Mutex::new(HashMap::new()),
+            arc_bboxes: OnceLock::new(),
+        }
+    }
+
+    /// Returns the wrapped topology.
+    pub fn topology(&self) -> &Topology {
+        &self.topology
+    }
+
+    /// Unwrap the topology, dropping the cached conversions.
+    pub fn into_inner(self) -> Topology {
+        self.topology
+    }
+
+    /// Convert the `key` object to a GeoJSON Feature collection (see [`to_geojson`]),
+    /// only decoding it on the first call for this key.
+    ///
+    /// Errors are not cached.
+    pub fn to_geojson(&self, key: &str) -> Result<SharedPtr<FeatureCollection>, Error> {
+        if let Some(fc) = self.cache.lock().unwrap().get(key) {
+            return Ok(SharedPtr::clone(fc));
+        }
+        // The lock isn't held during the conversion, so that other keys can be
+        // served meanwhile (concurrent conversions of the same key give equal results).
+        let fc = SharedPtr::new(to_geojson(&self.topology, key)?);
+        Ok(SharedPtr::clone(
+            self.cache
+                .lock()
+                .unwrap()
+                .entry(key.to_owned())
+                .or_insert(fc),
+        ))
+    }
+
+    /// The extents of the arcs of the topology (see [`Topology::arc_bboxes`]),
+    /// computed on the first call.
+    pub fn arc_bboxes(&self) -> &[[f64; 4]] {
+        self.arc_bboxes.get_or_init(|| self.topology.arc_bboxes())
+    }
+
+    /// Convert the features of the `key` object intersecting `bbox` to a GeoJSON
+    /// Feature collection (see [`to_geojson_in_bbox`](crate::to_geojson_in_bbox)),
+    /// filtering them on the cached extents of the arcs.
+    ///
+    /// The result depends on `bbox`, so it isn't cached.
+    pub fn to_geojson_in_bbox(
+        &self,
+        key: &str,
+        bbox: [f64; 4],
+    ) -> Result<FeatureCollection, Error> {
+        to_geojson_in_bbox_with_arc_bboxes(&self.topology, key, bbox, self.arc_bboxes())
+    }
+}
+
+impl From<Topology> for CachedTopology {
+    fn from(topology: Topology) -> Self {
+        CachedTopology::new(topology)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CachedTopology;
+    use crate::{to_geojson, to_geojson_in_bbox, Error, TopoJson};
+    use std::sync::Arc as SharedPtr;
+
+    #[test]
+    fn cached_conversions() {
+        let topo = "{\"arcs\":[[[2.2,2.2],[3.3,3.3]]],\"objects\":{\"example\":{\"arcs\":[0],\"type\":\"LineString\"}},\"type\":\"Topology\"}"
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        let cached = CachedTopology::new(topo.clone());
+
+        let first = cached.to_geojson("example").unwrap();
+        let second = cached.to_geojson("example").unwrap();
+        assert!(SharedPtr::ptr_eq(&first, &second));
+        assert_eq!(*first, to_geojson(&topo, "example").unwrap());
+
+        assert_eq!(
+            cached.to_geojson("foo"),
+            Err(Error::TopoToGeoUnknownKey("foo".to_string()))
+        );
+        assert_eq!(cached.into_inner(), topo);
+    }
+
+    #[test]
+    fn cached_arc_bboxes() {
+        let topo = "{\"arcs\":[[[0,0],[1,1]],[[10,10],[12,12]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"},{\"arcs\":[1],\"type\":\"LineString\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}"
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        let cached = CachedTopology::new(topo.clone());
+
+        assert_eq!(cached.arc_bboxes(), topo.arc_bboxes().as_slice());
+        assert!(std::ptr::eq(cached.arc_bboxes(), cached.arc_bboxes()));
+        let bbox = [0.5, 0.5, 8., 8.];
+        let fc = cached.to_geojson_in_bbox("example", bbox).unwrap();
+        assert_eq!(fc.features.len(), 1);
+        assert_eq!(fc, to_geojson_in_bbox(&topo, "example", bbox).unwrap());
+    }
+}
@@ -18,20 +18,58 @@
 pub enum Error {
     BboxExpectedArray,
     BboxExpectedNumericValues,
+    BboxNotFinite,
     TopologyExpectedObjects,
     TopologyExpectedArcs,
+    TopologyExpectedTransform,
     TransformExpectedScale,
     TransformExpectedTranslate,
     ScaleExpectedArray,
     ScaleExpectedNumericValues,
     TranslateExpectedArray,
     TranslateExpectedNumericValues,
+    TransformNotFinite,
+    TransformExpectedTwoValues,
+    TransformZeroScale,
+    InvalidQuantization(u32),
+    InvalidPrecision(u32),
+    PositionTooShort {
+        found: usize,
+    },
+    ArcIndexOutOfBounds(i64),
+    EmptyArcIndexes,
     TopoJsonUnknownType,
     GeometryUnknownType,
     MalformedJson,
+    InputTooLarge {
+        len: usize,
+        max_len: usize,
+    },
+    InvalidUtf8 {
+        valid_up_to: usize,
+    },
     PropertiesExpectedObjectOrNull,
-    ExpectedType { expected: String, actual: String },
+    ExpectedType {
+        expected: String,
+        actual: String,
+    },
     TopoToGeoUnknownKey(String),
+    JsonPointerNotFound(String),
+    DuplicateObjectName(String),
+    DuplicateFileName(String),
+    ObjectIsCollection(String),
+    ReservedForeignMemberKey(String),
+    ConversionThreadPanicked,
+    /// An error found within a nested object, at `path` (such as
+    /// `object 'countries', geometry 2`).
+    At {
+        path: String,
+        source: Box<Error>,
+    },
+    Io {
+        kind: std::io::ErrorKind,
+        message: String,
+    },
 
     // FIXME: make these types more specific
     ExpectedStringValue,
@@ -43,106 +81,291 @@ pub enum Error {
 }
 
 impl std::fmt::Display for Error {
+    #[cfg(feature = "errors-messages")]
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match *self {
-            Error::BboxExpectedArray => {
-                write!(f, "Encountered non-array type for a 'bbox' object.")
-            }
-            Error::BboxExpectedNumericValues => {
-                write!(f, "Encountered non-numeric value within 'bbox' array.")
-            }
-            Error::TopologyExpectedObjects => {
-                write!(f, "Expected member with the name 'objects' in Topology.")
-            }
-            Error::TopologyExpectedArcs => {
-                write!(f, "Expected member with the name 'arcs' in Topology.")
-            }
-            Error::TransformExpectedScale => {
-                write!(f, "Transform must have a member with the name 'scale'.")
-            }
-            Error::TransformExpectedTranslate => {
-                write!(f, "Transform must have a member with the name 'translate'.")
-            }
-            Error::ScaleExpectedArray => {
-                write!(f, "Encountered non-array type for a 'scale' object.")
-            }
-            Error::ScaleExpectedNumericValues => {
-                write!(f, "Encountered non-numeric value within 'scale' array.")
-            }
-            Error::TranslateExpectedArray => {
-                write!(f, "Encountered non-array type for a 'translate' object.")
-            }
-            Error::TranslateExpectedNumericValues => {
-                write!(f, "Encountered non-numeric value within 'translate' array.")
-            }
-            Error::TopoJsonUnknownType => write!(f, "Encountered unknown TopoJSON object type."),
-            Error::GeometryUnknownType => write!(f, "Encountered unknown 'geometry' object type."),
-            Error::MalformedJson =>
-            // FIXME: can we report specific serialization error?
-            {
-                write!(f, "Encountered malformed JSON.")
-            }
-            Error::PropertiesExpectedObjectOrNull =>
-            // FIXME: inform what type we actually found
-            {
-                write!(
-                    f,
-                    "Encountered neither object type nor null type for \
-                     'properties' object."
-                )
-            }
-            Error::ExpectedType {
-                ref expected,
-                ref actual,
-            } => write!(
-                f,
-                "Expected TopoJSON type '{}', found '{}'",
-                expected, actual,
-            ),
-            Error::TopoToGeoUnknownKey(ref key) => {
-                write!(f, "No object with key '{}' in the given Topology.", key)
-            }
-            Error::ExpectedStringValue => write!(f, "Expected a string value."),
-            Error::ExpectedProperty(ref prop_name) => {
-                write!(f, "Expected TopoJSON property '{}'.", prop_name)
-            }
-            Error::ExpectedF64Value => write!(f, "Expected a floating-point value."),
-            Error::Expectedi32Value => write!(f, "Expected a positive integer."),
-            Error::ExpectedArrayValue => write!(f, "Expected an array."),
-            Error::ExpectedObjectValue => write!(f, "Expected an object."),
+        self.write_message(f)
+    }
+
+    /// Without the `errors-messages` feature, only the variant and its data are shown.
+    #[cfg(not(feature = "errors-messages"))]
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io {
+            kind: error.kind(),
+            message: error.to_string(),
         }
     }
 }
 
 impl std::error::Error for Error {
     fn description(&self) -> &str {
+        #[cfg(feature = "errors-messages")]
+        {
+            self.summary()
+        }
+        #[cfg(not(feature = "errors-messages"))]
+        {
+            "TopoJSON error"
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
-            Error::BboxExpectedArray => "non-array 'bbox' type",
-            Error::BboxExpectedNumericValues => "non-numeric 'bbox' array",
-            Error::TopologyExpectedObjects => "no 'objects' member in topology",
-            Error::TopologyExpectedArcs => "no 'arcs' member in topology",
-            Error::TransformExpectedScale => "no 'scale' member in 'transform' member of topology",
-            Error::TransformExpectedTranslate => {
-                "no 'translate' member in 'transform' member of topology"
+            Error::At { ref source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// The human readable messages of the errors, which can be left out of the build
+/// by disabling the `errors-messages` feature (enabled by default).
+#[cfg(feature = "errors-messages")]
+mod messages {
+    use super::Error;
+
+    impl Error {
+        pub(super) fn write_message(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match *self {
+                Error::BboxExpectedArray => {
+                    write!(f, "Encountered non-array type for a 'bbox' object.")
+                }
+                Error::BboxExpectedNumericValues => {
+                    write!(f, "Encountered non-numeric value within 'bbox' array.")
+                }
+                Error::BboxNotFinite => {
+                    write!(f, "Encountered non-finite value within 'bbox' array.")
+                }
+                Error::TopologyExpectedObjects => {
+                    write!(f, "Expected member with the name 'objects' in Topology.")
+                }
+                Error::TopologyExpectedArcs => {
+                    write!(f, "Expected member with the name 'arcs' in Topology.")
+                }
+                Error::TopologyExpectedTransform => {
+                    write!(f, "Expected member with the name 'transform' in Topology.")
+                }
+                Error::TransformExpectedScale => {
+                    write!(f, "Transform must have a member with the name 'scale'.")
+                }
+                Error::TransformExpectedTranslate => {
+                    write!(f, "Transform must have a member with the name 'translate'.")
+                }
+                Error::ScaleExpectedArray => {
+                    write!(f, "Encountered non-array type for a 'scale' object.")
+                }
+                Error::ScaleExpectedNumericValues => {
+                    write!(f, "Encountered non-numeric value within 'scale' array.")
+                }
+                Error::TranslateExpectedArray => {
+                    write!(f, "Encountered non-array type for a 'translate' object.")
+                }
+                Error::TranslateExpectedNumericValues => {
+                    write!(f, "Encountered non-numeric value within 'translate' array.")
+                }
+                Error::TransformNotFinite => write!(
+                    f,
+                    "Encountered non-finite value within 'scale' or 'translate' array."
+                ),
+                Error::TransformExpectedTwoValues => write!(
+                    f,
+                    "Expected 'scale' and 'translate' to have (at least) two values."
+                ),
+                Error::TransformZeroScale => {
+                    write!(f, "Encountered a zero value within 'scale' array.")
+                }
+                Error::InvalidQuantization(q) => write!(
+                    f,
+                    "Invalid quantization parameter {}, it must be at least 2.",
+                    q
+                ),
+                Error::InvalidPrecision(d) => write!(
+                    f,
+                    "Invalid number of decimals {}, it must be at most 15.",
+                    d
+                ),
+                Error::PositionTooShort { found } => write!(
+                    f,
+                    "Encountered a position with {} component(s), expected at least 2.",
+                    found
+                ),
+                Error::ArcIndexOutOfBounds(ix) => {
+                    write!(f, "Encountered arc index {} referencing no arc.", ix)
+                }
+                Error::EmptyArcIndexes => {
+                    write!(f, "Encountered an empty array of arc indexes.")
+                }
+                Error::TopoJsonUnknownType => {
+                    write!(f, "Encountered unknown TopoJSON object type.")
+                }
+                Error::GeometryUnknownType => {
+                    write!(f, "Encountered unknown 'geometry' object type.")
+                }
+                Error::MalformedJson =>
+                // FIXME: can we report specific serialization error?
+                {
+                    write!(f, "Encountered malformed JSON.")
+                }
+                Error::InputTooLarge { len, max_len } => write!(
+                    f,
+                    "Encountered an array of {} items, more than the limit of {}.",
+                    len, max_len
+                ),
+                Error::InvalidUtf8 { valid_up_to } => write!(
+                    f,
+                    "Encountered invalid UTF-8 after the first {} byte(s).",
+                    valid_up_to
+                ),
+                Error::PropertiesExpectedObjectOrNull =>
+                // FIXME: inform what type we actually found
+                {
+                    write!(
+                        f,
+                        "Encountered neither object type nor null type for \
+                             'properties' object."
+                    )
+                }
+                Error::ExpectedType {
+                    ref expected,
+                    ref actual,
+                } => write!(
+                    f,
+                    "Expected TopoJSON type '{}', found '{}'",
+                    expected, actual,
+                ),
+                Error::TopoToGeoUnknownKey(ref key) => {
+                    write!(f, "No object with key '{}' in the given Topology.", key)
+                }
+                Error::JsonPointerNotFound(ref pointer) => {
+                    write!(f, "No value at JSON pointer '{}'.", pointer)
+                }
+                Error::DuplicateObjectName(ref name) => {
+                    write!(
+                        f,
+                        "An object named '{}' already exists in the Topology.",
+                        name
+                    )
+                }
+                Error::DuplicateFileName(ref file_name) => write!(
+                    f,
+                    "Several objects would be written to the file '{}'.",
+                    file_name
+                ),
+                Error::ObjectIsCollection(ref key) => write!(
+                    f,
+                    "The object with key '{}' is a geometry collection, not a single feature.",
+                    key
+                ),
+                Error::ReservedForeignMemberKey(ref key) => write!(
+                    f,
+                    "Foreign member '{}' has the name of a TopoJSON member.",
+                    key
+                ),
+                Error::ConversionThreadPanicked => {
+                    write!(f, "A thread converting the geometries panicked.")
+                }
+                Error::At {
+                    ref path,
+                    ref source,
+                } => write!(f, "In {}: {}", path, source),
+                Error::Io { ref message, .. } => write!(f, "I/O error: {}", message),
+                Error::ExpectedStringValue => write!(f, "Expected a string value."),
+                Error::ExpectedProperty(ref prop_name) => {
+                    write!(f, "Expected TopoJSON property '{}'.", prop_name)
+                }
+                Error::ExpectedF64Value => write!(f, "Expected a floating-point value."),
+                Error::Expectedi32Value => write!(f, "Expected a positive integer."),
+                Error::ExpectedArrayValue => write!(f, "Expected an array."),
+                Error::ExpectedObjectValue => write!(f, "Expected an object."),
             }
-            Error::ScaleExpectedArray => "non-array 'scale' type",
-            Error::ScaleExpectedNumericValues => "non-numeric 'scale' array",
-            Error::TranslateExpectedArray => "non-array 'translate' type",
-            Error::TranslateExpectedNumericValues => "non-numeric 'translate' array",
-            Error::TopoJsonUnknownType => "unknown TopoJSON object type",
-            Error::GeometryUnknownType => "unknown 'geometry' object type",
-            Error::MalformedJson => "malformed JSON",
-            Error::PropertiesExpectedObjectOrNull => {
-                "neither object type nor null type for properties' object."
+        }
+
+        pub(super) fn summary(&self) -> &'static str {
+            match *self {
+                Error::BboxExpectedArray => "non-array 'bbox' type",
+                Error::BboxExpectedNumericValues => "non-numeric 'bbox' array",
+                Error::BboxNotFinite => "non-finite 'bbox' array",
+                Error::TopologyExpectedObjects => "no 'objects' member in topology",
+                Error::TopologyExpectedArcs => "no 'arcs' member in topology",
+                Error::TopologyExpectedTransform => "no 'transform' member in topology",
+                Error::TransformExpectedScale => {
+                    "no 'scale' member in 'transform' member of topology"
+                }
+                Error::TransformExpectedTranslate => {
+                    "no 'translate' member in 'transform' member of topology"
+                }
+                Error::ScaleExpectedArray => "non-array 'scale' type",
+                Error::ScaleExpectedNumericValues => "non-numeric 'scale' array",
+                Error::TranslateExpectedArray => "non-array 'translate' type",
+                Error::TranslateExpectedNumericValues => "non-numeric 'translate' array",
+                Error::TransformNotFinite => "non-finite 'scale' or 'translate' array",
+                Error::TransformExpectedTwoValues => "'scale' or 'translate' array too short",
+                Error::TransformZeroScale => "zero value in 'scale' array",
+                Error::InvalidQuantization(..) => "invalid quantization parameter",
+                Error::InvalidPrecision(..) => "invalid number of decimals",
+                Error::PositionTooShort { .. } => "position with less than 2 components",
+                Error::ArcIndexOutOfBounds(..) => "arc index out of bounds",
+                Error::EmptyArcIndexes => "empty array of arc indexes",
+                Error::TopoJsonUnknownType => "unknown TopoJSON object type",
+                Error::GeometryUnknownType => "unknown 'geometry' object type",
+                Error::MalformedJson => "malformed JSON",
+                Error::InputTooLarge { .. } => "array too large",
+                Error::InvalidUtf8 { .. } => "invalid UTF-8",
+                Error::PropertiesExpectedObjectOrNull => {
+                    "neither object type nor null type for properties' object."
+                }
+                Error::ExpectedType { .. } => "mismatched TopoJSON type",
+                Error::TopoToGeoUnknownKey(..) => "requested key not found",
+                Error::JsonPointerNotFound(..) => "JSON pointer not found",
+                Error::DuplicateObjectName(..) => "duplicate object name",
+                Error::DuplicateFileName(..) => "duplicate file name",
+                Error::ObjectIsCollection(..) => "object is a geometry collection",
+                Error::ReservedForeignMemberKey(..) => "foreign member with a reserved name",
+                Error::ConversionThreadPanicked => "conversion thread panicked",
+                Error::At { ref source, .. } => source.summary(),
+                Error::Io { .. } => "I/O error",
+                Error::ExpectedStringValue => "expected a string value",
+                Error::ExpectedProperty(..) => "expected a TopoJSON property",
+                Error::ExpectedF64Value => "expected a floating-point value",
+                Error::Expectedi32Value => "expected a positive integer",
+                Error::ExpectedArrayValue => "expected an array",
+                Error::ExpectedObjectValue => "expected an object",
             }
-            Error::ExpectedType { .. } => "mismatched TopoJSON type",
-            Error::TopoToGeoUnknownKey(..) => "requested key not found",
-            Error::ExpectedStringValue => "expected a string value",
-            Error::ExpectedProperty(..) => "expected a TopoJSON property",
-            Error::ExpectedF64Value => "expected a floating-point value",
-            Error::Expectedi32Value => "expected a positive integer",
-            Error::ExpectedArrayValue => "expected an array",
-            Error::ExpectedObjectValue => "expected an object",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[cfg(feature = "errors-messages")]
+    #[test]
+    fn display_messages() {
+        assert_eq!(
+            Error::TopoToGeoUnknownKey("foo".to_string()).to_string(),
+            "No object with key 'foo' in the given Topology."
+        );
+        assert_eq!(
+            Error::At {
+                path: String::from("object 'countries', geometry 2"),
+                source: Box::new(Error::ExpectedProperty(String::from("arcs"))),
+            }
+            .to_string(),
+            "In object 'countries', geometry 2: Expected TopoJSON property 'arcs'."
+        );
+    }
+
+    #[cfg(not(feature = "errors-messages"))]
+    #[test]
+    fn display_without_messages() {
+        assert_eq!(
+            Error::TopoToGeoUnknownKey("foo".to_string()).to_string(),
+            "TopoToGeoUnknownKey(\"foo\")"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright 2018 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::util::validate_positions;
+use crate::{
+    Arc, ArcIndexes, Error, Geometry, NamedGeometry, Position, Topology, Value as TopoJsonGeomValue,
+};
+use geojson::{Geometry as GeoJsonGeometry, Value as GeoJsonGeomValue};
+
+/// Store `line` as a new arc and return the arc indexes referencing it.
+fn push_arc(arcs: &mut Vec<Arc>, line: &[Position]) -> ArcIndexes {
+    arcs.push(line.to_vec());
+    vec![(arcs.len() - 1) as i32]
+}
+
+fn convert_geom_value(value: &GeoJsonGeomValue, arcs: &mut Vec<Arc>) -> TopoJsonGeomValue {
+    match value {
+        GeoJsonGeomValue::Point(pos) => TopoJsonGeomValue::Point(pos.to_vec()),
+        GeoJsonGeomValue::MultiPoint(positions) => {
+            TopoJsonGeomValue::MultiPoint(positions.iter().map(|pos| pos.to_vec()).collect())
+        }
+        GeoJsonGeomValue::LineString(line) => TopoJsonGeomValue::LineString(push_arc(arcs, line)),
+        GeoJsonGeomValue::MultiLineString(lines) => TopoJsonGeomValue::MultiLineString(
+            lines.iter().map(|line| push_arc(arcs, line)).collect(),
+        ),
+        GeoJsonGeomValue::Polygon(rings) => {
+            TopoJsonGeomValue::Polygon(rings.iter().map(|ring| push_arc(arcs, ring)).collect())
+        }
+        GeoJsonGeomValue::MultiPolygon(polygons) => TopoJsonGeomValue::MultiPolygon(
+            polygons
+                .iter()
+                .map(|rings| rings.iter().map(|ring| push_arc(arcs, ring)).collect())
+                .collect(),
+        ),
+        GeoJsonGeomValue::GeometryCollection(geometries) => TopoJsonGeomValue::GeometryCollection(
+            geometries
+                .iter()
+                .map(|g| Geometry::new(convert_geom_value(&g.value, arcs)))
+                .collect(),
+        ),
+    }
+}
+
+/// Build a Topology containing a single object, named `object_name`, from a GeoJSON Geometry.
+///
+/// Every LineString and every polygon ring is stored as its own arc (no shared arcs are
+/// detected), without quantization, and the object has no properties.
+///
+/// Fails with [`Error::PositionTooShort`] if a position of the geometry has less
+/// than two components.
+pub fn topology_from_geometry(
+    geom: &GeoJsonGeometry,
+    object_name: &str,
+) -> Result<Topology, Error> {
+    let mut arcs = Vec::new();
+    let value = convert_geom_value(&geom.value, &mut arcs);
+    let topo = Topology {
+        arcs,
+        objects: vec![NamedGeometry {
+            name: object_name.to_owned(),
+            geometry: Geometry::new(value),
+        }],
+        ..Default::default()
+    };
+    validate_positions(&topo)?;
+    Ok(topo)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{to_geojson, topology_from_geometry, Error, Value};
+    use geojson::{Geometry as GeoJsonGeometry, Value as GeoJsonGeomValue};
+
+    #[test]
+    fn convert_single_polygon() {
+        let polygon = GeoJsonGeomValue::Polygon(vec![
+            vec![
+                vec![0.0, 0.0],
+                vec![4.0, 0.0],
+                vec![4.0, 4.0],
+                vec![0.0, 4.0],
+                vec![0.0, 0.0],
+            ],
+            vec![
+                vec![1.0, 1.0],
+                vec![1.0, 2.0],
+                vec![2.0, 2.0],
+                vec![1.0, 1.0],
+            ],
+        ]);
+        let topo = topology_from_geometry(&GeoJsonGeometry::new(polygon.clone()), "square")
+            .expect("Unable to convert to TopoJson");
+
+        assert_eq!(topo.list_names(), vec!["square"]);
+        assert_eq!(topo.arcs.len(), 2);
+        assert_eq!(topo.arcs[1][0], vec![1.0, 1.0]);
+        assert_eq!(
+            topo.objects[0].geometry.value,
+            Value::Polygon(vec![vec![0], vec![1]])
+        );
+        assert_eq!(topo.objects[0].geometry.properties, None);
+
+        let geojson_obj = to_geojson(&topo, "square").unwrap();
+        assert_eq!(geojson_obj.features.len(), 1);
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            polygon
+        );
+    }
+
+    #[test]
+    fn convert_short_position() {
+        let line = GeoJsonGeomValue::LineString(vec![vec![0.0, 0.0], vec![1.0]]);
+        assert_eq!(
+            topology_from_geometry(&GeoJsonGeometry::new(line), "line"),
+            Err(Error::PositionTooShort { found: 1 })
+        );
+
+        let points = GeoJsonGeomValue::GeometryCollection(vec![GeoJsonGeometry::new(
+            GeoJsonGeomValue::MultiPoint(vec![vec![0.0, 0.0], vec![]]),
+        )]);
+        assert_eq!(
+            topology_from_geometry(&GeoJsonGeometry::new(points), "points"),
+            Err(Error::PositionTooShort { found: 0 })
+        );
+    }
+}
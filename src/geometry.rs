@@ -12,9 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
+
 use crate::json::{Deserialize, Deserializer, JsonObject, JsonValue, Serialize, Serializer};
 
-use crate::{topojson::Type, util, ArcIndexes, Bbox, Error, Position};
+use crate::{
+    algorithm, member_names, to_geojson, topojson::Type, util, Arc, ArcIndexes, Bbox, Error,
+    ParseOptions, Position, TransformParams,
+};
 
 /// The underlying Geometry value (which may contain Position or Arc indexes)
 #[derive(Clone, Debug, PartialEq)]
@@ -53,6 +58,13 @@ pub enum Value {
     ///
     /// [TopoJSON Format Specification § 3.1.8](https://github.com/topojson/topojson-specification#227-geometry-collection)
     GeometryCollection(Vec<Geometry>),
+
+    /// A geometry whose type isn't known by this crate, holding its raw JSON object
+    /// (including the 'type' member).
+    ///
+    /// It is only produced when parsing with
+    /// [`ParseOptions::unknown_geometry_passthrough`](crate::ParseOptions) enabled.
+    Unknown(JsonObject),
 }
 
 impl Value {
@@ -65,11 +77,45 @@ impl Value {
             Value::Polygon(ref x) => ::serde_json::to_value(x),
             Value::MultiPolygon(ref x) => ::serde_json::to_value(x),
             Value::GeometryCollection(ref x) => ::serde_json::to_value(x),
+            Value::Unknown(ref x) => ::serde_json::to_value(x),
         }
         .unwrap()
     }
 }
 
+impl Value {
+    /// The TopoJSON type of the geometry ('Point', 'LineString', etc.).
+    pub(crate) fn type_name(&self) -> &str {
+        match self {
+            Value::Point(..) => "Point",
+            Value::MultiPoint(..) => "MultiPoint",
+            Value::LineString(..) => "LineString",
+            Value::MultiLineString(..) => "MultiLineString",
+            Value::Polygon(..) => "Polygon",
+            Value::MultiPolygon(..) => "MultiPolygon",
+            Value::GeometryCollection(..) => "GeometryCollection",
+            Value::Unknown(ref raw) => match raw.get(member_names::TYPE) {
+                Some(JsonValue::String(ref t)) => t,
+                _ => "Unknown",
+            },
+        }
+    }
+}
+
+impl Value {
+    /// The name of the member holding the value ('coordinates', 'arcs' or 'geometries').
+    pub(crate) fn member_name(&self) -> &'static str {
+        match self {
+            Value::GeometryCollection(..) => member_names::GEOMETRIES,
+            Value::LineString(..)
+            | Value::MultiLineString(..)
+            | Value::Polygon(..)
+            | Value::MultiPolygon(..) => member_names::ARCS,
+            _ => member_names::COORDINATES,
+        }
+    }
+}
+
 impl Serialize for Value {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -107,41 +153,173 @@ impl Geometry {
             foreign_members: None,
         }
     }
+
+    /// Returns the value of the property `key`, if any.
+    pub fn property(&self, key: &str) -> Option<&JsonValue> {
+        self.properties.as_ref().and_then(|props| props.get(key))
+    }
+
+    /// Set the value of the property `key`, creating the 'properties' member if needed.
+    pub fn set_property(&mut self, key: &str, value: JsonValue) {
+        self.properties
+            .get_or_insert_with(JsonObject::new)
+            .insert(key.to_owned(), value);
+    }
+
+    /// Remove the property `key`, returning its value if it was set.
+    pub fn remove_property(&mut self, key: &str) -> Option<JsonValue> {
+        self.properties.as_mut().and_then(|props| props.remove(key))
+    }
+
+    /// Whether `key` is the name of a member of this geometry in the specification,
+    /// which a foreign member can't have.
+    pub(crate) fn is_reserved_member(&self, key: &str) -> bool {
+        [
+            member_names::TYPE,
+            member_names::BBOX,
+            member_names::PROPERTIES,
+            member_names::ID,
+            self.value.member_name(),
+        ]
+        .contains(&key)
+    }
+
+    /// Compute the extent of the geometry, as `[minx, miny, maxx, maxy]`, from its
+    /// point coordinates and the arcs it references in `arcs` (decoded with `transform`),
+    /// descending into geometry collections.
+    ///
+    /// Returns `None` if the geometry doesn't contain any position. Arc indexes
+    /// referencing no arc are ignored.
+    pub fn bbox_of(&self, arcs: &[Arc], transform: Option<TransformParams>) -> Option<Bbox> {
+        let mut extent = None;
+        self.for_each_position(&mut |pos| {
+            algorithm::extend_bbox(&mut extent, &to_geojson::make_pt(pos, transform))
+        });
+        self.for_each_arc_index(&mut |ix| {
+            if let Some(arc) = arcs.get(util::normalize_arc_index(ix)) {
+                for pos in to_geojson::decode_arc(arc, transform) {
+                    algorithm::extend_bbox(&mut extent, &pos);
+                }
+            }
+        });
+        extent.map(|e| e.to_vec())
+    }
+
+    /// Call `f` on this geometry and on every geometry it contains
+    /// (descending into geometry collections).
+    pub(crate) fn for_each_geometry<F: FnMut(&Geometry)>(&self, f: &mut F) {
+        f(self);
+        if let Value::GeometryCollection(ref geoms) = self.value {
+            geoms.iter().for_each(|g| g.for_each_geometry(f));
+        }
+    }
+
+    /// Call `f` on this geometry and on every geometry it contains
+    /// (descending into geometry collections).
+    pub(crate) fn for_each_geometry_mut<F: FnMut(&mut Geometry)>(&mut self, f: &mut F) {
+        f(self);
+        if let Value::GeometryCollection(ref mut geoms) = self.value {
+            geoms.iter_mut().for_each(|g| g.for_each_geometry_mut(f));
+        }
+    }
+
+    /// Call `f` on every position stored in the 'coordinates' member of this
+    /// geometry (for Point and MultiPoint, descending into geometry collections).
+    pub(crate) fn for_each_position<F: FnMut(&Position)>(&self, f: &mut F) {
+        match self.value {
+            Value::Point(ref pos) => f(pos),
+            Value::MultiPoint(ref positions) => positions.iter().for_each(f),
+            Value::GeometryCollection(ref geoms) => {
+                geoms.iter().for_each(|g| g.for_each_position(f))
+            }
+            _ => {}
+        }
+    }
+
+    /// Call `f` on a mutable reference to every position stored in the 'coordinates'
+    /// member of this geometry (descending into geometry collections).
+    pub(crate) fn for_each_position_mut<F: FnMut(&mut Position)>(&mut self, f: &mut F) {
+        match self.value {
+            Value::Point(ref mut pos) => f(pos),
+            Value::MultiPoint(ref mut positions) => positions.iter_mut().for_each(f),
+            Value::GeometryCollection(ref mut geoms) => {
+                geoms.iter_mut().for_each(|g| g.for_each_position_mut(f))
+            }
+            _ => {}
+        }
+    }
+
+    /// Call `f` on every arc index referenced by this geometry
+    /// (descending into geometry collections), in the order they appear.
+    pub(crate) fn for_each_arc_index<F: FnMut(i32)>(&self, f: &mut F) {
+        match self.value {
+            Value::Point(..) | Value::MultiPoint(..) | Value::Unknown(..) => {}
+            Value::LineString(ref ixs) => ixs.iter().for_each(|ix| f(*ix)),
+            Value::MultiLineString(ref ixs) | Value::Polygon(ref ixs) => {
+                ixs.iter().flatten().for_each(|ix| f(*ix))
+            }
+            Value::MultiPolygon(ref ixs) => ixs.iter().flatten().flatten().for_each(|ix| f(*ix)),
+            Value::GeometryCollection(ref geoms) => {
+                geoms.iter().for_each(|g| g.for_each_arc_index(f))
+            }
+        }
+    }
+
+    /// Call `f` on a mutable reference to every arc index referenced by this geometry
+    /// (descending into geometry collections), in the order they appear.
+    pub(crate) fn for_each_arc_index_mut<F: FnMut(&mut i32)>(&mut self, f: &mut F) {
+        match self.value {
+            Value::Point(..) | Value::MultiPoint(..) | Value::Unknown(..) => {}
+            Value::LineString(ref mut ixs) => ixs.iter_mut().for_each(f),
+            Value::MultiLineString(ref mut ixs) | Value::Polygon(ref mut ixs) => {
+                ixs.iter_mut().flatten().for_each(f)
+            }
+            Value::MultiPolygon(ref mut ixs) => ixs.iter_mut().flatten().flatten().for_each(f),
+            Value::GeometryCollection(ref mut geoms) => {
+                geoms.iter_mut().for_each(|g| g.for_each_arc_index_mut(f))
+            }
+        }
+    }
+}
+
+impl Default for Geometry {
+    /// Returns an empty GeometryCollection, with all the other members set to `None`.
+    fn default() -> Self {
+        Geometry::new(Value::GeometryCollection(vec![]))
+    }
 }
 
 impl<'a> From<&'a Geometry> for JsonObject {
     fn from(geometry: &'a Geometry) -> JsonObject {
+        // Unknown geometries are written back as they were read:
+        if let Value::Unknown(ref raw) = geometry.value {
+            return raw.clone();
+        }
         let mut map = JsonObject::new();
         if let Some(ref bbox) = geometry.bbox {
-            map.insert(String::from("bbox"), ::serde_json::to_value(bbox).unwrap());
+            map.insert(
+                String::from(member_names::BBOX),
+                ::serde_json::to_value(bbox).unwrap(),
+            );
         }
 
-        let ty = String::from(match geometry.value {
-            Value::Point(..) => "Point",
-            Value::MultiPoint(..) => "MultiPoint",
-            Value::LineString(..) => "LineString",
-            Value::MultiLineString(..) => "MultiLineString",
-            Value::Polygon(..) => "Polygon",
-            Value::MultiPolygon(..) => "MultiPolygon",
-            Value::GeometryCollection(..) => "GeometryCollection",
-        });
+        let ty = String::from(geometry.value.type_name());
 
-        map.insert(String::from("type"), ::serde_json::to_value(&ty).unwrap());
+        map.insert(
+            String::from(member_names::TYPE),
+            ::serde_json::to_value(&ty).unwrap(),
+        );
 
         map.insert(
-            String::from(match geometry.value {
-                Value::GeometryCollection(..) => "geometries",
-                Value::LineString(..)
-                | Value::MultiLineString(..)
-                | Value::Polygon(..)
-                | Value::MultiPolygon(..) => "arcs",
-                _ => "coordinates",
-            }),
+            String::from(geometry.value.member_name()),
             ::serde_json::to_value(&geometry.value).unwrap(),
         );
 
         if let Some(ref id) = geometry.id {
-            map.insert(String::from("id"), serde_json::to_value(id).unwrap());
+            map.insert(
+                String::from(member_names::ID),
+                serde_json::to_value(id).unwrap(),
+            );
         }
 
         if let Some(ref properties) = geometry.properties {
@@ -150,13 +328,16 @@ impl<'a> From<&'a Geometry> for JsonObject {
                 prop.insert(key.to_owned(), value.to_owned());
             }
             map.insert(
-                String::from("properties"),
+                String::from(member_names::PROPERTIES),
                 ::serde_json::Value::Object(prop),
             );
         }
+        // Foreign members never replace (or stand for) the members of the specification:
         if let Some(ref foreign_members) = geometry.foreign_members {
             for (key, value) in foreign_members {
-                map.insert(key.to_owned(), value.to_owned());
+                if !geometry.is_reserved_member(key) {
+                    map.insert(key.to_owned(), value.to_owned());
+                }
             }
         }
         map
@@ -164,20 +345,37 @@ impl<'a> From<&'a Geometry> for JsonObject {
 }
 
 impl Geometry {
-    pub fn from_json_object(mut object: JsonObject) -> Result<Self, Error> {
-        let value = match &Type::from_str(&util::expect_type(&mut object)?)
-            .ok_or(Error::TopoJsonUnknownType)?
-        {
-            Type::Point => Value::Point(util::get_coords_one_pos(&mut object)?),
-            Type::MultiPoint => Value::MultiPoint(util::get_coords_1d_pos(&mut object)?),
-            Type::LineString => Value::LineString(util::get_arc_ix(&mut object)?),
-            Type::MultiLineString => Value::MultiLineString(util::get_arc_ix_1d(&mut object)?),
-            Type::Polygon => Value::Polygon(util::get_arc_ix_1d(&mut object)?),
-            Type::MultiPolygon => Value::MultiPolygon(util::get_arc_ix_2d(&mut object)?),
-            Type::GeometryCollection => {
-                Value::GeometryCollection(util::get_geometries(&mut object)?)
+    pub fn from_json_object(object: JsonObject) -> Result<Self, Error> {
+        Geometry::from_json_object_with_options(object, &ParseOptions::default())
+    }
+
+    pub fn from_json_object_with_options(
+        mut object: JsonObject,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        let type_ = util::expect_type(&mut object)?;
+        let value = match Type::from_str(&type_) {
+            Some(Type::Point) => Value::Point(util::get_coords_one_pos(&mut object, options)?),
+            Some(Type::MultiPoint) => {
+                Value::MultiPoint(util::get_coords_1d_pos(&mut object, options)?)
+            }
+            Some(Type::LineString) => Value::LineString(util::get_arc_ix(&mut object, options)?),
+            Some(Type::MultiLineString) => {
+                Value::MultiLineString(util::get_arc_ix_1d(&mut object, options)?)
             }
-            _ => return Err(Error::GeometryUnknownType),
+            Some(Type::Polygon) => Value::Polygon(util::get_arc_ix_1d(&mut object, options)?),
+            Some(Type::MultiPolygon) => {
+                Value::MultiPolygon(util::get_arc_ix_2d(&mut object, options)?)
+            }
+            Some(Type::GeometryCollection) => {
+                Value::GeometryCollection(util::get_geometries(&mut object, options)?)
+            }
+            Some(Type::Topology) => return Err(Error::GeometryUnknownType),
+            None if options.unknown_geometry_passthrough => {
+                object.insert(String::from(member_names::TYPE), JsonValue::String(type_));
+                return Ok(Geometry::new(Value::Unknown(object)));
+            }
+            None => return Err(Error::TopoJsonUnknownType),
         };
         Ok(Geometry {
             value,
@@ -212,6 +410,14 @@ impl<'de> Deserialize<'de> for Geometry {
     }
 }
 
+impl fmt::Display for Geometry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        ::serde_json::to_string(self)
+            .map_err(|_| fmt::Error)
+            .and_then(|s| f.write_str(&s))
+    }
+}
+
 /// One member of the 'objects' member of a Topology
 ///
 /// [TopoJSON Format Specification § 2.1.5](https://github.com/topojson/topojson-specification#215-objects)
@@ -224,7 +430,7 @@ pub struct NamedGeometry {
 #[cfg(test)]
 mod tests {
     use crate::json::JsonObject;
-    use crate::{Error, Geometry, TopoJson, Value};
+    use crate::{Error, Geometry, ParseOptions, TopoJson, TransformParams, Value};
 
     fn encode(geometry: &Geometry) -> String {
         serde_json::to_string(&geometry).unwrap()
@@ -270,6 +476,27 @@ mod tests {
         assert_eq!(decoded_geometry, geometry);
     }
 
+    #[test]
+    fn encode_decode_geometry_ids() {
+        for (id_str, id) in [
+            ("42", serde_json::json!(42)),
+            ("\"42\"", serde_json::json!("42")),
+            ("4.5", serde_json::json!(4.5)),
+            ("null", serde_json::Value::Null),
+        ] {
+            let geometry_json_str = format!(
+                "{{\"coordinates\":[1.0,2.0],\"id\":{},\"type\":\"Point\"}}",
+                id_str
+            );
+            let geometry = decode(geometry_json_str.clone())
+                .try_unwrap_geometry()
+                .unwrap();
+            // The JSON type of the id is kept:
+            assert_eq!(geometry.id, Some(id));
+            assert_eq!(encode(&geometry), geometry_json_str);
+        }
+    }
+
     #[test]
     fn encode_decode_geometry_with_arc_indexes_polygon() {
         let geometry_json_str = "{\"arcs\":[[1]],\"type\":\"Polygon\"}";
@@ -418,4 +645,137 @@ mod tests {
         };
         assert_eq!(decoded_geometry, geometry_collection);
     }
+
+    #[test]
+    fn default_geometry() {
+        let geometry = Geometry::default();
+        assert_eq!(geometry, Geometry::new(Value::GeometryCollection(vec![])));
+        assert_eq!(
+            encode(&geometry),
+            "{\"geometries\":[],\"type\":\"GeometryCollection\"}"
+        );
+    }
+
+    #[test]
+    fn set_get_remove_property() {
+        let mut geometry = Geometry::new(Value::Point(vec![1.1, 2.1]));
+        assert_eq!(geometry.property("prop0"), None);
+        assert_eq!(geometry.remove_property("prop0"), None);
+
+        geometry.set_property("prop0", serde_json::to_value(0).unwrap());
+        assert_eq!(
+            geometry.property("prop0"),
+            Some(&serde_json::to_value(0).unwrap())
+        );
+        assert_eq!(
+            encode(&geometry),
+            "{\"coordinates\":[1.1,2.1],\"properties\":{\"prop0\":0},\"type\":\"Point\"}"
+        );
+
+        assert_eq!(
+            geometry.remove_property("prop0"),
+            Some(serde_json::to_value(0).unwrap())
+        );
+        assert_eq!(geometry.property("prop0"), None);
+    }
+
+    #[test]
+    fn display_geometry() {
+        let mut geometry = Geometry::new(Value::Polygon(vec![vec![0, -2]]));
+        geometry.set_property("name", serde_json::json!("a"));
+
+        let displayed = format!("{}", geometry);
+        assert_eq!(
+            displayed,
+            "{\"arcs\":[[0,-2]],\"properties\":{\"name\":\"a\"},\"type\":\"Polygon\"}"
+        );
+        assert_eq!(decode(displayed).try_unwrap_geometry().unwrap(), geometry);
+    }
+
+    #[test]
+    fn encode_decode_geometry_large_integer_property() {
+        // Not representable exactly as a f64:
+        let geometry_json_str =
+            "{\"coordinates\":[1.0,2.0],\"properties\":{\"id\":9007199254740993},\"type\":\"Point\"}";
+        let geometry = decode(geometry_json_str.to_string())
+            .try_unwrap_geometry()
+            .unwrap();
+        assert_eq!(
+            geometry.property("id").unwrap().as_u64(),
+            Some(9007199254740993)
+        );
+        assert_eq!(encode(&geometry), geometry_json_str);
+    }
+
+    #[cfg(feature = "arbitrary-precision")]
+    #[test]
+    fn encode_decode_geometry_arbitrary_precision_property() {
+        // Out of the range of u64 and with more digits than a f64 has:
+        let geometry_json_str = "{\"coordinates\":[1.0,2.0],\"properties\":{\"big\":123456789012345678901234567890,\"precise\":0.12345678901234567890123},\"type\":\"Point\"}";
+        let geometry = decode(geometry_json_str.to_string())
+            .try_unwrap_geometry()
+            .unwrap();
+        assert_eq!(encode(&geometry), geometry_json_str);
+    }
+
+    #[test]
+    fn decode_unknown_geometry_passthrough() {
+        let topo_json_str = "{\"arcs\":[],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[1.0,2.0],\"type\":\"Point\"},{\"control\":[[0,0],[1,1]],\"id\":3,\"type\":\"Curve\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}";
+        let error = topo_json_str.parse::<TopoJson>().err().unwrap();
+        assert_eq!(
+            error,
+            Error::At {
+                path: String::from("object 'example', geometry 1"),
+                source: Box::new(Error::TopoJsonUnknownType),
+            }
+        );
+        #[cfg(feature = "errors-messages")]
+        assert_eq!(
+            error.to_string(),
+            "In object 'example', geometry 1: Encountered unknown TopoJSON object type."
+        );
+
+        let options = ParseOptions {
+            unknown_geometry_passthrough: true,
+            ..Default::default()
+        };
+        let topo = TopoJson::from_str_with_options(topo_json_str, &options)
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        let geoms = match topo.objects[0].geometry.value {
+            Value::GeometryCollection(ref geoms) => geoms,
+            _ => panic!(),
+        };
+        match geoms[1].value {
+            Value::Unknown(ref raw) => assert_eq!(raw.get("type"), Some(&json!("Curve"))),
+            _ => panic!(),
+        }
+        assert_eq!(serde_json::to_string(&topo).unwrap(), topo_json_str);
+
+        // Only the Point is converted:
+        let fc = crate::to_geojson(&topo, "example").unwrap();
+        assert_eq!(fc.features.len(), 1);
+    }
+
+    #[test]
+    fn bbox_of() {
+        // Quantized arcs, the second one being delta-encoded from [2, 0]:
+        let arcs = vec![
+            vec![vec![0., 0.], vec![2., 4.]],
+            vec![vec![2., 0.], vec![-2., 1.]],
+        ];
+        let transform = Some(TransformParams {
+            scale: [0.5, 0.5],
+            translate: [10., 20.],
+        });
+        let line = Geometry::new(Value::LineString(vec![0, -2]));
+        assert_eq!(
+            line.bbox_of(&arcs, transform),
+            Some(vec![10., 20., 11., 22.])
+        );
+        // Without transform, the arcs are not delta-encoded:
+        assert_eq!(line.bbox_of(&arcs, None), Some(vec![-2., 0., 2., 4.]));
+        assert_eq!(Geometry::default().bbox_of(&arcs, transform), None);
+    }
 }
@@ -0,0 +1,233 @@
+// Copyright 2018 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::io::Read;
+
+use serde::ser::SerializeMap;
+use serde_json::value::RawValue;
+
+use crate::json::{JsonObject, Serialize, Serializer};
+use crate::{
+    member_names, util, Arc, Bbox, Error, NamedGeometry, ParseOptions, Topology, TransformParams,
+};
+
+/// How the arcs of a [`LazyTopology`] are stored.
+#[derive(Clone, Debug)]
+enum ArcStorage {
+    /// The raw JSON text of the 'arcs' member, along with the arcs
+    /// once they have been parsed.
+    Raw(Box<RawValue>, OnceCell<Vec<Arc>>),
+    Parsed(Vec<Arc>),
+}
+
+/// A Topology whose 'arcs' member is only parsed when accessed.
+///
+/// It is obtained with [`Topology::from_reader_lazy`] and is meant for workflows which
+/// only need the metadata and objects of a topology, or re-emit it unchanged: the arcs,
+/// which are usually most of the file, are kept as raw JSON text and serialized as is.
+#[derive(Clone, Debug)]
+pub struct LazyTopology {
+    pub bbox: Option<Bbox>,
+    pub objects: Vec<NamedGeometry>,
+    pub transform: Option<TransformParams>,
+    pub foreign_members: Option<JsonObject>,
+    arcs: ArcStorage,
+}
+
+fn parse_arcs(raw: &RawValue) -> Result<Vec<Arc>, Error> {
+    let mut object = JsonObject::new();
+    let arcs_json = serde_json::from_str(raw.get()).map_err(|_| Error::MalformedJson)?;
+    object.insert(String::from(member_names::ARCS), arcs_json);
+    util::get_arcs_position(&mut object, &ParseOptions::default())
+}
+
+impl LazyTopology {
+    /// Returns the arcs of the topology, parsing them on first access.
+    pub fn arcs(&self) -> Result<&[Arc], Error> {
+        match self.arcs {
+            ArcStorage::Parsed(ref arcs) => Ok(arcs),
+            ArcStorage::Raw(ref raw, ref cell) => {
+                if let Some(arcs) = cell.get() {
+                    return Ok(arcs);
+                }
+                let arcs = parse_arcs(raw)?;
+                Ok(cell.get_or_init(|| arcs))
+            }
+        }
+    }
+
+    /// Whether the arcs have already been parsed.
+    pub fn arcs_parsed(&self) -> bool {
+        match self.arcs {
+            ArcStorage::Parsed(..) => true,
+            ArcStorage::Raw(_, ref cell) => cell.get().is_some(),
+        }
+    }
+
+    /// Convert to a regular [`Topology`], parsing the arcs if needed.
+    pub fn into_topology(self) -> Result<Topology, Error> {
+        let arcs = match self.arcs {
+            ArcStorage::Parsed(arcs) => arcs,
+            ArcStorage::Raw(raw, cell) => match cell.into_inner() {
+                Some(arcs) => arcs,
+                None => parse_arcs(&raw)?,
+            },
+        };
+        Ok(Topology {
+            bbox: self.bbox,
+            objects: self.objects,
+            transform: self.transform,
+            arcs,
+            foreign_members: self.foreign_members,
+        })
+    }
+}
+
+impl From<Topology> for LazyTopology {
+    fn from(topo: Topology) -> Self {
+        LazyTopology {
+            bbox: topo.bbox,
+            objects: topo.objects,
+            transform: topo.transform,
+            foreign_members: topo.foreign_members,
+            arcs: ArcStorage::Parsed(topo.arcs),
+        }
+    }
+}
+
+impl Topology {
+    /// Read a Topology from `reader`, leaving its 'arcs' member unparsed until
+    /// [`LazyTopology::arcs`] is called.
+    pub fn from_reader_lazy<R: Read>(reader: R) -> Result<LazyTopology, Error> {
+        let mut members: BTreeMap<String, Box<RawValue>> =
+            serde_json::from_reader(reader).map_err(util::from_json_error)?;
+        let raw_arcs = members
+            .remove(member_names::ARCS)
+            .ok_or(Error::TopologyExpectedArcs)?;
+
+        let mut object = JsonObject::new();
+        let mut objects_order = Vec::new();
+        for (key, raw) in members {
+            let member: util::OrderedMember =
+                serde_json::from_str(raw.get()).map_err(|_| Error::MalformedJson)?;
+            if key == member_names::OBJECTS {
+                objects_order = member.keys;
+            }
+            object.insert(key, member.value);
+        }
+        let options = ParseOptions::default();
+        match util::expect_type(&mut object)? {
+            ref type_ if type_ == "Topology" => {
+                let bbox = util::get_bbox(&mut object)?;
+                let mut objects = util::get_objects(&mut object, &options)?;
+                util::restore_objects_order(&mut objects, &objects_order);
+                Ok(LazyTopology {
+                    bbox,
+                    objects,
+                    transform: util::get_scale_translate(&mut object, &options)?,
+                    arcs: ArcStorage::Raw(raw_arcs, OnceCell::new()),
+                    foreign_members: util::get_foreign_members(object)?,
+                })
+            }
+            type_ => Err(Error::ExpectedType {
+                expected: "Topology".to_owned(),
+                actual: type_,
+            }),
+        }
+    }
+}
+
+impl Serialize for LazyTopology {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Build the other members like a Topology would, then insert
+        // the arcs (as is) at the place they would have in its output:
+        let topo = Topology {
+            bbox: self.bbox.clone(),
+            objects: self.objects.clone(),
+            transform: self.transform,
+            arcs: vec![],
+            foreign_members: self.foreign_members.clone(),
+        };
+        let mut members = JsonObject::from(&topo);
+        members.remove(member_names::ARCS);
+
+        let mut map = serializer.serialize_map(Some(members.len() + 1))?;
+        let mut arcs_written = false;
+        for (key, value) in members.iter() {
+            if !arcs_written && key.as_str() > member_names::ARCS {
+                self.serialize_arcs(&mut map)?;
+                arcs_written = true;
+            }
+            map.serialize_entry(key, value)?;
+        }
+        if !arcs_written {
+            self.serialize_arcs(&mut map)?;
+        }
+        map.end()
+    }
+}
+
+impl LazyTopology {
+    fn serialize_arcs<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        match self.arcs {
+            ArcStorage::Raw(ref raw, _) => map.serialize_entry(member_names::ARCS, raw),
+            ArcStorage::Parsed(ref arcs) => map.serialize_entry(member_names::ARCS, arcs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, TopoJson, Topology};
+
+    #[test]
+    fn read_lazy_topology() {
+        let topo_json_str = "{\"arcs\":[[[4000,0],[1999,9999],[2000,-9999],[2000,9999]]],\"objects\":{\"example\":{\"arcs\":[0],\"type\":\"LineString\"}},\"other_member\":true,\"transform\":{\"scale\":[0.0005,0.0001],\"translate\":[100.0,0.0]},\"type\":\"Topology\"}";
+        let lazy = Topology::from_reader_lazy(topo_json_str.as_bytes()).unwrap();
+        assert_eq!(lazy.objects.len(), 1);
+        assert!(lazy.transform.is_some());
+        assert!(!lazy.arcs_parsed());
+
+        // The arcs are written back untouched (integers aren't turned into floats):
+        assert_eq!(serde_json::to_string(&lazy).unwrap(), topo_json_str);
+
+        let expected = topo_json_str
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(lazy.arcs().unwrap(), expected.arcs.as_slice());
+        assert!(lazy.arcs_parsed());
+        assert_eq!(lazy.into_topology().unwrap(), expected);
+    }
+
+    #[test]
+    fn read_lazy_topology_errors() {
+        let result =
+            Topology::from_reader_lazy("{\"objects\":{},\"type\":\"Topology\"}".as_bytes());
+        assert_eq!(result.err(), Some(Error::TopologyExpectedArcs));
+
+        // Malformed arcs are only reported on access:
+        let lazy = Topology::from_reader_lazy(
+            "{\"arcs\":[0],\"objects\":{},\"type\":\"Topology\"}".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(lazy.arcs().err(), Some(Error::ExpectedArrayValue));
+    }
+}
@@ -149,19 +149,54 @@ pub type Arc = Vec<Position>;
 /// [TopoJSON Format Specification $ 2.1.4](https://github.com/topojson/topojson-specification#214-arc-indexes)
 pub type ArcIndexes = Vec<i32>;
 
+mod arc_ref;
+pub use crate::arc_ref::ArcRef;
+
+pub(crate) mod algorithm;
+pub mod member_names;
 pub(crate) mod util;
+pub use crate::util::{arc_key, arc_key_reversed};
 
 mod topojson;
-pub use crate::topojson::TopoJson;
+pub use crate::topojson::{ParseOptions, TopoJson, DEFAULT_MAX_ARRAY_LEN};
 
 mod geometry;
 pub use crate::geometry::{Geometry, NamedGeometry, Value};
 
 mod topology;
-pub use crate::topology::{Topology, TransformParams};
+pub use crate::topology::{MemberOrder, PolygonMetrics, Topology, TransformParams};
+
+mod from_geojson;
+pub use crate::from_geojson::topology_from_geometry;
+
+mod lazy_topology;
+pub use crate::lazy_topology::LazyTopology;
+
+mod cached_topology;
+pub use crate::cached_topology::CachedTopology;
+
+mod quantize;
+
+mod typed_topology;
+pub use crate::typed_topology::{
+    Geographic, GeographicTopology, Quantized, QuantizedTopology, TypedTopology,
+};
 
 mod to_geojson;
-pub use crate::to_geojson::to_geojson;
+#[cfg(feature = "parallel")]
+pub use crate::to_geojson::to_geojson_parallel;
+pub use crate::to_geojson::{
+    geom_value_to_geojson, to_feature, to_geojson, to_geojson_all, to_geojson_by_type,
+    to_geojson_clipped, to_geojson_filtered, to_geojson_in_bbox,
+    to_geojson_in_bbox_with_arc_bboxes, to_geojson_keys, to_geojson_lenient,
+    to_geojson_with_options, to_geojson_with_reproject, write_geojson, write_geojson_files,
+    ToGeoJsonOptions,
+};
+
+#[cfg(feature = "wkt")]
+mod to_wkt;
+#[cfg(feature = "wkt")]
+pub use crate::to_wkt::to_wkt;
 
 mod error;
 pub use crate::error::Error;
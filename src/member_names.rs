@@ -0,0 +1,46 @@
+// Copyright 2018 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Names of the members of the TopoJSON objects defined by the specification.
+//!
+//! [TopoJSON Format Specification § 2](https://github.com/topojson/topojson-specification#2-topojson-objects)
+
+/// The type of every TopoJSON object
+pub const TYPE: &str = "type";
+/// The bounding box of every TopoJSON object
+pub const BBOX: &str = "bbox";
+
+/// The arcs of a Topology, or the arc indexes of a LineString, MultiLineString,
+/// Polygon or MultiPolygon geometry
+pub const ARCS: &str = "arcs";
+/// The named geometries of a Topology
+pub const OBJECTS: &str = "objects";
+/// The transform of a quantized Topology
+pub const TRANSFORM: &str = "transform";
+/// The scale of a transform
+pub const SCALE: &str = "scale";
+/// The translate of a transform
+pub const TRANSLATE: &str = "translate";
+
+/// The positions of a Point or MultiPoint geometry
+pub const COORDINATES: &str = "coordinates";
+/// The members of a GeometryCollection
+pub const GEOMETRIES: &str = "geometries";
+/// The properties of a geometry
+pub const PROPERTIES: &str = "properties";
+/// The identifier of a geometry
+pub const ID: &str = "id";
+
+/// The members of a Topology defined by the specification
+pub const TOPOLOGY_MEMBERS: [&str; 5] = [TYPE, BBOX, ARCS, OBJECTS, TRANSFORM];
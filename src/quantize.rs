@@ -0,0 +1,694 @@
+// Copyright 2018 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::{extend_bbox, positions_approx_eq};
+use crate::to_geojson::{decode_arc, make_pt};
+use crate::util::{check_finite, normalize_arc_index, validate_positions};
+use crate::{Arc, Bbox, Error, Geometry, Position, Topology, TransformParams};
+
+/// Quantize a position with the transform `tr`, in which the
+/// quantized coordinates are multiplied by `k` (the inverse of the scale).
+fn quantize_pt(pos: &[f64], tr: &TransformParams, k: [f64; 2]) -> Position {
+    let mut new_pos = pos.to_vec();
+    new_pos[0] = ((pos[0] - tr.translate[0]) * k[0]).round();
+    new_pos[1] = ((pos[1] - tr.translate[1]) * k[1]).round();
+    new_pos
+}
+
+const EMPTY_EXTENT: [f64; 4] = [
+    f64::INFINITY,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    f64::NEG_INFINITY,
+];
+
+/// The extent of a decoded arc (the empty extent if it has no position).
+fn arc_bbox(arc: &[Position], tr: Option<TransformParams>) -> [f64; 4] {
+    let mut extent = None;
+    for pos in decode_arc(arc, tr) {
+        extend_bbox(&mut extent, &pos);
+    }
+    extent.unwrap_or(EMPTY_EXTENT)
+}
+
+/// The transform of a `quantization` × `quantization` grid fitted to `bbox`,
+/// with the inverses of its scale.
+fn fit_transform(bbox: [f64; 4], quantization: u32) -> (TransformParams, [f64; 2]) {
+    let n = f64::from(quantization - 1);
+    let kx = if bbox[2] > bbox[0] {
+        n / (bbox[2] - bbox[0])
+    } else {
+        1.
+    };
+    let ky = if bbox[3] > bbox[1] {
+        n / (bbox[3] - bbox[1])
+    } else {
+        1.
+    };
+    let tr = TransformParams {
+        scale: [1. / kx, 1. / ky],
+        translate: [bbox[0], bbox[1]],
+    };
+    (tr, [kx, ky])
+}
+
+/// Quantize and delta-encode an arc of absolute positions (those with less
+/// than two components are kept as they are, as `decode_arc` does).
+fn quantize_arc(arc: &mut Arc, tr: &TransformParams, k: [f64; 2]) {
+    let (mut x0, mut y0) = (0., 0.);
+    for pos in arc.iter_mut() {
+        if pos.len() < 2 {
+            continue;
+        }
+        let mut new_pos = quantize_pt(pos, tr, k);
+        let (x, y) = (new_pos[0], new_pos[1]);
+        new_pos[0] -= x0;
+        new_pos[1] -= y0;
+        x0 = x;
+        y0 = y;
+        *pos = new_pos;
+    }
+}
+
+impl Topology {
+    /// Iterate over every decoded (real-world) position of the topology: the
+    /// positions of each arc, followed by the point coordinates of the objects.
+    ///
+    /// As arcs are shared, each arc position is yielded once, whatever the
+    /// number of geometries referencing its arc.
+    pub fn positions_iter(&self) -> impl Iterator<Item = Position> + '_ {
+        let mut points = Vec::new();
+        for named_geom in self.objects.iter() {
+            named_geom
+                .geometry
+                .for_each_position(&mut |pos| points.push(make_pt(pos, self.transform)));
+        }
+        self.arcs
+            .iter()
+            .flat_map(move |arc| decode_arc(arc, self.transform))
+            .chain(points)
+    }
+
+    /// Compute the extent (`[minx, miny, maxx, maxy]`) of each decoded arc, in the order
+    /// of `self.arcs`. An empty arc gives the empty extent `[∞, ∞, -∞, -∞]`.
+    ///
+    /// The result is meant to be computed once and kept along the topology, so that
+    /// the extent of a geometry can be obtained by merging the extents of its arcs
+    /// rather than by decoding them again.
+    pub fn arc_bboxes(&self) -> Vec<[f64; 4]> {
+        self.arcs
+            .iter()
+            .map(|arc| arc_bbox(arc, self.transform))
+            .collect()
+    }
+
+    /// Like [`Topology::arc_bboxes`], only decoding the arcs referenced by `geometry`:
+    /// the other arcs are given the empty extent.
+    pub(crate) fn arc_bboxes_of(&self, geometry: &Geometry) -> Vec<[f64; 4]> {
+        let mut arc_bboxes = vec![EMPTY_EXTENT; self.arcs.len()];
+        let mut computed = vec![false; self.arcs.len()];
+        geometry.for_each_arc_index(&mut |ix| {
+            let i = normalize_arc_index(ix);
+            if i < self.arcs.len() && !computed[i] {
+                arc_bboxes[i] = arc_bbox(&self.arcs[i], self.transform);
+                computed[i] = true;
+            }
+        });
+        arc_bboxes
+    }
+
+    /// The extent of a geometry of the topology, obtained from the `arc_bboxes`
+    /// of the arcs it references and from its point coordinates.
+    ///
+    /// Returns `None` if the geometry doesn't contain any position.
+    pub(crate) fn geometry_extent(
+        &self,
+        geometry: &Geometry,
+        arc_bboxes: &[[f64; 4]],
+    ) -> Option<[f64; 4]> {
+        let mut extent = None;
+        geometry
+            .for_each_position(&mut |pos| extend_bbox(&mut extent, &make_pt(pos, self.transform)));
+        // Indexes referencing no arc are left to be reported by the conversion:
+        geometry.for_each_arc_index(&mut |ix| match arc_bboxes.get(normalize_arc_index(ix)) {
+            Some(e) if e[0] <= e[2] => {
+                extend_bbox(&mut extent, &e[..2]);
+                extend_bbox(&mut extent, &e[2..]);
+            }
+            _ => {}
+        });
+        extent
+    }
+
+    /// Compute the extent of the topology, as `[minx, miny, maxx, maxy]`, from its
+    /// decoded arcs and point coordinates (the 'bbox' member isn't used).
+    ///
+    /// Returns `None` if the topology doesn't contain any position.
+    pub fn bounding_box(&self) -> Option<Bbox> {
+        let mut bbox = None;
+        for pos in self.positions_iter() {
+            extend_bbox(&mut bbox, &pos);
+        }
+        bbox.map(|b| b.to_vec())
+    }
+
+    /// Convert the arcs and the point coordinates to real-world coordinates,
+    /// and remove the transform.
+    ///
+    /// Nothing is done when the topology has no transform, so this can be called
+    /// unconditionally (dequantizing twice is the same as dequantizing once).
+    pub fn dequantize(&mut self) {
+        let tr = match self.transform.take() {
+            Some(tr) => Some(tr),
+            None => return,
+        };
+        for arc in self.arcs.iter_mut() {
+            *arc = decode_arc(arc, tr);
+        }
+        for named_geom in self.objects.iter_mut() {
+            named_geom
+                .geometry
+                .for_each_position_mut(&mut |pos| *pos = make_pt(pos, tr));
+        }
+    }
+
+    /// Dequantize the topology if its transform is the identity (see
+    /// [`TransformParams::is_identity`]), so that it is serialized without a
+    /// 'transform' member. The positions are left unchanged, but the arcs are no
+    /// longer delta-encoded.
+    ///
+    /// Returns whether the transform was removed.
+    pub fn remove_identity_transform(&mut self) -> bool {
+        match self.transform {
+            Some(ref tr) if tr.is_identity() => {
+                self.dequantize();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Quantize the topology with the `target` transform, dequantizing it first if it
+    /// already has a transform, so that its arcs can be combined with those of another
+    /// topology quantized with `target`.
+    ///
+    /// The positions are rounded to the grid of `target`, so they move by at most half
+    /// of its scale. The 'bbox' member is left untouched.
+    ///
+    /// Fails, leaving the topology untouched, with [`Error::TransformNotFinite`] or
+    /// [`Error::TransformZeroScale`] if `target` can't be inverted, and with
+    /// [`Error::PositionTooShort`] if a position has less than two components.
+    pub fn transform_to(&mut self, target: &TransformParams) -> Result<(), Error> {
+        check_finite(&target.scale, Error::TransformNotFinite)?;
+        check_finite(&target.translate, Error::TransformNotFinite)?;
+        if target.scale.contains(&0.) {
+            return Err(Error::TransformZeroScale);
+        }
+        validate_positions(self)?;
+        self.dequantize();
+        let k = [1. / target.scale[0], 1. / target.scale[1]];
+        for arc in self.arcs.iter_mut() {
+            quantize_arc(arc, target, k);
+        }
+        for named_geom in self.objects.iter_mut() {
+            named_geom
+                .geometry
+                .for_each_position_mut(&mut |pos| *pos = quantize_pt(pos, target, k));
+        }
+        self.transform = Some(*target);
+        Ok(())
+    }
+
+    /// Scale and translate the topology so that its extent (computed from its positions)
+    /// maps onto `[0, width] × [0, height]`, for example to draw it on a canvas.
+    ///
+    /// The topology is dequantized first. With `flip_y`, the y axis points down as in
+    /// screen coordinates. The 'bbox' member, if any, is set to the canvas.
+    /// Nothing is done when the topology doesn't contain any position, and the
+    /// positions with less than two components are left as is.
+    pub fn scale_to_fit(&mut self, width: f64, height: f64, flip_y: bool) {
+        self.dequantize();
+        let extent = match self.bounding_box() {
+            Some(b) => b,
+            None => return,
+        };
+        // The positions along an axis without extent are all mapped to its origin:
+        let factor = |size: f64, min: f64, max: f64| {
+            if max > min {
+                size / (max - min)
+            } else {
+                0.
+            }
+        };
+        let (kx, ky) = (
+            factor(width, extent[0], extent[2]),
+            factor(height, extent[1], extent[3]),
+        );
+        let fit = |pos: &mut Position| {
+            if pos.len() < 2 {
+                return;
+            }
+            pos[0] = (pos[0] - extent[0]) * kx;
+            pos[1] = (pos[1] - extent[1]) * ky;
+            if flip_y {
+                pos[1] = height - pos[1];
+            }
+        };
+        self.arcs.iter_mut().flatten().for_each(fit);
+        for named_geom in self.objects.iter_mut() {
+            named_geom
+                .geometry
+                .for_each_position_mut(&mut |pos| fit(pos));
+        }
+        if self.bbox.is_some() {
+            self.bbox = Some(vec![0., 0., width, height]);
+        }
+    }
+
+    /// Compare the decoded arcs and point coordinates of two topologies, which may
+    /// differ by at most `epsilon`, whatever their transforms.
+    ///
+    /// Unlike [`Topology::approx_eq`], arcs are compared one by one (so they must be
+    /// in the same order and split the same way) and the point coordinates are
+    /// compared in the order of the objects.
+    pub fn same_geometry(&self, other: &Topology, epsilon: f64) -> bool {
+        let same_line = |a: &[Position], b: &[Position]| {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(u, v)| positions_approx_eq(u, v, epsilon))
+        };
+        let points = |topo: &Topology| {
+            let mut points = Vec::new();
+            for named_geom in topo.objects.iter() {
+                named_geom
+                    .geometry
+                    .for_each_position(&mut |pos| points.push(make_pt(pos, topo.transform)));
+            }
+            points
+        };
+        self.arcs.len() == other.arcs.len()
+            && self.arcs.iter().zip(other.arcs.iter()).all(|(a, b)| {
+                same_line(
+                    &decode_arc(a, self.transform),
+                    &decode_arc(b, other.transform),
+                )
+            })
+            && same_line(&points(self), &points(other))
+    }
+
+    /// Quantize and delta-encode arcs of absolute (real-world) positions, on a grid
+    /// of `quantization` × `quantization` positions fitted to their extent.
+    ///
+    /// Returns the encoded arcs with the transform to decode them, for building
+    /// a topology arc-first.
+    ///
+    /// Fails with [`Error::InvalidQuantization`] if `quantization` is less than 2.
+    pub fn encode_arcs(
+        mut absolute_arcs: Vec<Arc>,
+        quantization: u32,
+    ) -> Result<(Vec<Arc>, TransformParams), Error> {
+        if quantization < 2 {
+            return Err(Error::InvalidQuantization(quantization));
+        }
+        let mut bbox = None;
+        for pos in absolute_arcs.iter().flatten() {
+            extend_bbox(&mut bbox, pos);
+        }
+        let (tr, k) = fit_transform(bbox.unwrap_or([0., 0., 0., 0.]), quantization);
+        for arc in absolute_arcs.iter_mut() {
+            quantize_arc(arc, &tr, k);
+        }
+        Ok((absolute_arcs, tr))
+    }
+
+    /// Quantize the topology to a grid of `quantization` × `quantization` positions
+    /// fitted to its bbox, dequantizing it first if it already has a transform (so the
+    /// arcs of a quantized topology are never delta-encoded twice).
+    ///
+    /// The 'bbox' member is used if present (only its x and y ranges, for a bbox with
+    /// more dimensions), otherwise or if it isn't a valid bbox it is computed (and
+    /// stored). Coordinates are only rounded once, on the new grid, so requantizing at
+    /// the same resolution gives the same coordinates.
+    ///
+    /// Fails with [`Error::PositionTooShort`] (leaving the topology untouched) if a
+    /// position has less than two components.
+    pub fn requantize(&mut self, quantization: u32) -> Result<(), Error> {
+        if quantization < 2 {
+            return Err(Error::InvalidQuantization(quantization));
+        }
+        validate_positions(self)?;
+        self.dequantize();
+        let bbox = match self.bbox {
+            // `[minx, miny, (minz, ...,) maxx, maxy, (maxz, ...)]`:
+            Some(ref b) if b.len() >= 4 && b.len() % 2 == 0 => {
+                let half = b.len() / 2;
+                [b[0], b[1], b[half], b[half + 1]]
+            }
+            _ => match self.bounding_box() {
+                Some(b) => {
+                    self.bbox = Some(b.clone());
+                    [b[0], b[1], b[2], b[3]]
+                }
+                // Nothing to quantize:
+                None => [0., 0., 0., 0.],
+            },
+        };
+
+        let (tr, k) = fit_transform(bbox, quantization);
+        for arc in self.arcs.iter_mut() {
+            quantize_arc(arc, &tr, k);
+        }
+        for named_geom in self.objects.iter_mut() {
+            named_geom
+                .geometry
+                .for_each_position_mut(&mut |pos| *pos = quantize_pt(pos, &tr, k));
+        }
+        self.transform = Some(tr);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::{geom_value_extent, positions_approx_eq};
+    use crate::to_geojson::decode_arc;
+    use crate::{
+        to_geojson, to_geojson_in_bbox, Error, TopoJson, Topology, TransformParams, Value,
+    };
+
+    fn spec_example() -> Topology {
+        // This is the non-quantized example from https://github.com/topojson/topojson-specification#11-examples
+        "{\"arcs\":[[[102,0],[103,1],[104,0],[105,1]],[[100,0],[101,0],[101,1],[100,1],[100,0]]],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[102,0.5],\"type\":\"Point\"},{\"arcs\":[0],\"type\":\"LineString\"},{\"arcs\":[[1]],\"type\":\"Polygon\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}"
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap()
+    }
+
+    fn assert_same_coordinates(a: &Topology, b: &Topology, epsilon: f64) {
+        assert!(a.same_geometry(b, epsilon));
+        assert!(a.approx_eq(b, epsilon));
+    }
+
+    #[test]
+    fn bounding_box() {
+        assert_eq!(
+            spec_example().bounding_box(),
+            Some(vec![100.0, 0.0, 105.0, 1.0])
+        );
+        assert_eq!(Topology::default().bounding_box(), None);
+    }
+
+    #[test]
+    fn extents_ignore_short_positions() {
+        let topo = "{\"arcs\":[[[0,0],[1]],[[2,2],[3,4]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"},{\"coordinates\":[],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}"
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(topo.bounding_box(), Some(vec![0.0, 0.0, 3.0, 4.0]));
+        assert_eq!(
+            topo.arc_bboxes(),
+            vec![[0.0, 0.0, 0.0, 0.0], [2.0, 2.0, 3.0, 4.0]]
+        );
+        let fc = to_geojson_in_bbox(&topo, "example", [-1., -1., 1., 1.]).unwrap();
+        assert_eq!(fc.features.len(), 1);
+    }
+
+    #[test]
+    fn positions_iter() {
+        let topo = spec_example();
+        // 4 + 5 arc positions and a point:
+        assert_eq!(topo.positions_iter().count(), 10);
+        assert_eq!(topo.positions_iter().last(), Some(vec![102.0, 0.5]));
+
+        let mut quantized = topo.clone();
+        quantized.requantize(10_000).unwrap();
+        for (u, v) in quantized.positions_iter().zip(topo.positions_iter()) {
+            assert!(positions_approx_eq(&u, &v, 1e-3));
+        }
+    }
+
+    #[test]
+    fn geometry_extent_from_arc_bboxes() {
+        let mut topo = spec_example();
+        topo.requantize(10_000).unwrap();
+        let arc_bboxes = topo.arc_bboxes();
+        assert_eq!(arc_bboxes.len(), 2);
+
+        let geoms = match topo.objects[0].geometry.value {
+            Value::GeometryCollection(ref geoms) => geoms,
+            _ => unreachable!(),
+        };
+        let features = to_geojson(&topo, "example").unwrap().features;
+        for (geom, feature) in geoms.iter().zip(features) {
+            assert_eq!(
+                topo.geometry_extent(geom, &arc_bboxes),
+                geom_value_extent(&feature.geometry.unwrap().value)
+            );
+        }
+    }
+
+    #[test]
+    fn requantize() {
+        let geographic = spec_example();
+
+        let mut topo = geographic.clone();
+        topo.requantize(10_000).unwrap();
+        let tr = topo.transform.unwrap();
+        assert_eq!(tr.translate, [100.0, 0.0]);
+        assert_eq!(topo.bbox, Some(vec![100.0, 0.0, 105.0, 1.0]));
+        // Arcs are delta-encoded:
+        assert_eq!(topo.arcs[1][0], vec![0.0, 0.0]);
+        assert_eq!(topo.arcs[1][1], vec![2000.0, 0.0]);
+        assert_same_coordinates(&topo, &geographic, 1e-3);
+
+        // Going to a finer grid keeps the coordinates, and coming back
+        // to the original grid gives the same arcs again:
+        let coarse = topo.clone();
+        topo.requantize(1_000_000).unwrap();
+        assert_same_coordinates(&topo, &coarse, 1e-5);
+        topo.requantize(10_000).unwrap();
+        assert_eq!(topo, coarse);
+        topo.requantize(10_000).unwrap();
+        assert_eq!(topo, coarse);
+
+        assert_eq!(topo.requantize(1), Err(Error::InvalidQuantization(1)));
+
+        let mut short = geographic.clone();
+        short.arcs[0][1] = vec![103.0];
+        let before = short.clone();
+        assert_eq!(
+            short.requantize(10_000),
+            Err(Error::PositionTooShort { found: 1 })
+        );
+        assert_eq!(short, before);
+    }
+
+    #[test]
+    fn requantize_3d_bbox() {
+        let mut topo = spec_example();
+        topo.bbox = Some(vec![100.0, 0.0, -5.0, 105.0, 1.0, 5.0]);
+        topo.requantize(10_000).unwrap();
+        let tr = topo.transform.unwrap();
+        assert_eq!(tr.translate, [100.0, 0.0]);
+        assert_eq!(tr.scale, [5.0 / 9999.0, 1.0 / 9999.0]);
+        assert_eq!(topo.bbox, Some(vec![100.0, 0.0, -5.0, 105.0, 1.0, 5.0]));
+        assert_same_coordinates(&topo, &spec_example(), 1e-3);
+
+        // A bbox with an odd number of values is replaced by the computed one:
+        let mut topo = spec_example();
+        topo.bbox = Some(vec![100.0, 0.0, 105.0, 1.0, 5.0]);
+        topo.requantize(10_000).unwrap();
+        assert_eq!(topo.bbox, Some(vec![100.0, 0.0, 105.0, 1.0]));
+        assert_eq!(topo.transform.unwrap().scale, [5.0 / 9999.0, 1.0 / 9999.0]);
+    }
+
+    #[test]
+    fn encode_arcs() {
+        let arcs = spec_example().arcs;
+        let (encoded, tr) = Topology::encode_arcs(arcs.clone(), 1000).unwrap();
+        assert_eq!(tr.translate, [100.0, 0.0]);
+        assert_eq!(encoded[0][0], vec![400.0, 0.0]);
+        assert_eq!(encoded[0][1], vec![199.0, 999.0]);
+
+        let decoded: Vec<_> = encoded
+            .iter()
+            .map(|arc| decode_arc(arc, Some(tr)))
+            .collect();
+        for (arc, decoded_arc) in arcs.iter().zip(decoded.iter()) {
+            assert_eq!(arc.len(), decoded_arc.len());
+            for (u, v) in arc.iter().zip(decoded_arc.iter()) {
+                assert!(positions_approx_eq(u, v, 1e-2), "{:?} != {:?}", u, v);
+            }
+        }
+
+        assert_eq!(
+            Topology::encode_arcs(arcs, 1),
+            Err(Error::InvalidQuantization(1))
+        );
+        assert_eq!(
+            Topology::encode_arcs(vec![], 0),
+            Err(Error::InvalidQuantization(0))
+        );
+    }
+
+    #[test]
+    fn same_geometry_at_two_resolutions() {
+        let mut coarse = spec_example();
+        coarse.requantize(1_000).unwrap();
+        let mut fine = spec_example();
+        fine.requantize(100_000).unwrap();
+
+        assert_ne!(coarse, fine);
+        assert!(coarse.same_geometry(&fine, 1e-2));
+        assert!(fine.same_geometry(&spec_example(), 1e-4));
+        assert!(!coarse.same_geometry(&fine, 1e-6));
+
+        let mut moved = spec_example();
+        moved.arcs[0][1][1] += 0.5;
+        assert!(!moved.same_geometry(&fine, 1e-2));
+    }
+
+    #[test]
+    fn scale_to_fit() {
+        // A unit square, quantized:
+        let mut topo = "{\"arcs\":[[[0,0],[10,0],[0,10],[-10,0],[0,-10]]],\"objects\":{\"square\":{\"arcs\":[[0]],\"type\":\"Polygon\"},\"center\":{\"coordinates\":[5,5],\"type\":\"Point\"}},\"transform\":{\"scale\":[0.1,0.1],\"translate\":[2,3]},\"type\":\"Topology\"}"
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+
+        let mut flipped = topo.clone();
+        topo.scale_to_fit(256., 256., false);
+        assert_eq!(topo.transform, None);
+        assert_eq!(
+            topo.arcs[0],
+            vec![
+                vec![0., 0.],
+                vec![256., 0.],
+                vec![256., 256.],
+                vec![0., 256.],
+                vec![0., 0.]
+            ]
+        );
+        assert_eq!(
+            topo.objects[1].geometry.value,
+            Value::Point(vec![128., 128.])
+        );
+
+        flipped.scale_to_fit(256., 256., true);
+        assert_eq!(flipped.arcs[0][1], vec![256., 256.]);
+        assert_eq!(flipped.arcs[0][2], vec![256., 0.]);
+
+        let mut short = flipped.clone();
+        short.arcs[0].push(vec![7.]);
+        short.scale_to_fit(512., 512., true);
+        assert_eq!(short.arcs[0][1], vec![512., 0.]);
+        assert_eq!(short.arcs[0][5], vec![7.]);
+    }
+
+    #[test]
+    fn remove_identity_transform() {
+        let topo_json_str = "{\"arcs\":[[[1,2],[3,0],[0,4]]],\"objects\":{},\"transform\":{\"scale\":[1.0,1.0],\"translate\":[0.0,0.0]},\"type\":\"Topology\"}";
+        let mut topo = topo_json_str
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert!(topo.transform.unwrap().is_identity());
+        let decoded = topo.positions_iter().collect::<Vec<_>>();
+
+        assert!(topo.remove_identity_transform());
+        assert_eq!(
+            serde_json::to_string(&topo).unwrap(),
+            "{\"arcs\":[[[1.0,2.0],[4.0,2.0],[4.0,6.0]]],\"objects\":{},\"type\":\"Topology\"}"
+        );
+        assert_eq!(topo.positions_iter().collect::<Vec<_>>(), decoded);
+        assert!(!topo.remove_identity_transform());
+
+        let mut scaled = topo_json_str
+            .replace("[1.0,1.0]", "[2.0,1.0]")
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert!(!scaled.remove_identity_transform());
+        assert!(scaled.transform.is_some());
+    }
+
+    #[test]
+    fn transform_to() {
+        let topo = "{\"arcs\":[[[0,0],[4,0],[0,4],[-4,0],[0,-4]]],\"objects\":{\"square\":{\"arcs\":[[0]],\"type\":\"Polygon\"},\"center\":{\"coordinates\":[2,2],\"type\":\"Point\"}},\"transform\":{\"scale\":[0.5,0.5],\"translate\":[10,20]},\"type\":\"Topology\"}"
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        let target = TransformParams {
+            scale: [0.25, 0.1],
+            translate: [9., 19.5],
+        };
+
+        let mut rebased = topo.clone();
+        rebased.transform_to(&target).unwrap();
+        assert_eq!(rebased.transform, Some(target));
+        assert_eq!(
+            rebased.arcs[0],
+            vec![
+                vec![4., 5.],
+                vec![8., 0.],
+                vec![0., 20.],
+                vec![-8., 0.],
+                vec![0., -20.]
+            ]
+        );
+        assert_eq!(
+            rebased.objects[1].geometry.value,
+            Value::Point(vec![8., 15.])
+        );
+        assert!(rebased.same_geometry(&topo, 1e-9));
+
+        let invalid = [
+            ([0., 0.25], Error::TransformZeroScale),
+            ([f64::NAN, 0.25], Error::TransformNotFinite),
+        ];
+        for (scale, error) in invalid {
+            let target = TransformParams { scale, ..target };
+            assert_eq!(rebased.clone().transform_to(&target), Err(error));
+        }
+        let mut short = topo.clone();
+        short.arcs[0].push(vec![1.]);
+        let before = short.clone();
+        assert_eq!(
+            short.transform_to(&target),
+            Err(Error::PositionTooShort { found: 1 })
+        );
+        assert_eq!(short, before);
+    }
+
+    #[test]
+    fn dequantize_without_transform() {
+        let mut topo = spec_example();
+        topo.dequantize();
+        assert_eq!(topo, spec_example());
+
+        // Once dequantized, a quantized topology is left as is:
+        topo.requantize(1000).unwrap();
+        topo.dequantize();
+        let dequantized = topo.clone();
+        topo.dequantize();
+        assert_eq!(topo, dequantized);
+    }
+}
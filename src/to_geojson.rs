@@ -12,15 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::json::JsonValue;
+use crate::json::{JsonObject, JsonValue};
 use crate::{
-    Arc, Error, Geometry, NamedGeometry, Position, Topology, TransformParams,
+    algorithm, Arc, ArcRef, Error, Geometry, NamedGeometry, Position, Topology, TransformParams,
     Value as TopoJsonGeomValue,
 };
 use geojson::feature::Id as FeatureId;
-use geojson::{Feature, FeatureCollection, Geometry as GeoJsonGeometry, Value as GeoJsonGeomValue};
+use geojson::{
+    Feature, FeatureCollection, GeoJson, Geometry as GeoJsonGeometry, Value as GeoJsonGeomValue,
+};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 
-fn decode_arc(arc: &[Position], tr: &Option<TransformParams>) -> Vec<Position> {
+// `TransformParams` is `Copy` (four f64), so the decoding functions take it by value:
+// the scale and translate are then read from locals rather than through a reference
+// in the per-position loops.
+//
+// With a transform, the positions of an arc are delta-encoded (each one is relative to
+// the previous one) while the coordinates of Point and MultiPoint geometries aren't:
+// `decode_arc` accumulates the positions before applying the transform, `make_pt` only
+// applies it. Positions with less than two components (which `Topology::validate`
+// reports) are given back as is rather than indexed out of bounds.
+pub(crate) fn decode_arc(arc: &[Position], tr: Option<TransformParams>) -> Vec<Position> {
     match tr {
         None => arc.to_vec(),
         Some(_tr) => {
@@ -34,6 +49,10 @@ fn decode_arc(arc: &[Position], tr: &Option<TransformParams>) -> Vec<Position> {
             let (mut x, mut y) = (0., 0.);
             for pt in arc {
                 let mut new_pt = pt.clone();
+                if new_pt.len() < 2 {
+                    ring.push(new_pt);
+                    continue;
+                }
                 x += new_pt[0];
                 y += new_pt[1];
                 new_pt[0] = x * s0 + t0;
@@ -45,70 +64,82 @@ fn decode_arc(arc: &[Position], tr: &Option<TransformParams>) -> Vec<Position> {
     }
 }
 
-fn make_pt(pos: &[f64], tr: &Option<TransformParams>) -> Vec<f64> {
+pub(crate) fn make_pt(pos: &[f64], tr: Option<TransformParams>) -> Vec<f64> {
     match tr {
         None => pos.to_vec(),
-        Some(_tr) => {
+        Some(_tr) if pos.len() >= 2 => {
             let mut new_pos = pos.to_vec();
             new_pos[0] = new_pos[0] * _tr.scale[0] + _tr.translate[0];
             new_pos[1] = new_pos[1] * _tr.scale[1] + _tr.translate[1];
             new_pos
         }
+        Some(_) => pos.to_vec(),
     }
 }
 
-fn make_feature_id(id: Option<JsonValue>) -> Option<FeatureId> {
+/// GeoJSON feature ids are either a string or a number: those keep their JSON type,
+/// and any other id (including null) gives a feature without id.
+fn make_feature_id(id: &Option<JsonValue>) -> Option<FeatureId> {
     match id {
-        Some(JsonValue::Number(i)) => Some(FeatureId::Number(i)),
-        Some(JsonValue::String(i)) => Some(FeatureId::String(i)),
+        Some(JsonValue::Number(i)) => Some(FeatureId::Number(i.clone())),
+        Some(JsonValue::String(i)) => Some(FeatureId::String(i.clone())),
         _ => None,
     }
 }
 
-pub fn convert_geom_coords(
-    geom: &Geometry,
-    tr: &Option<TransformParams>,
-) -> Result<Feature, Error> {
-    let geoj_geom_value = match &geom.value {
-        TopoJsonGeomValue::Point(ref pos) => GeoJsonGeomValue::Point(make_pt(pos, tr)),
-        TopoJsonGeomValue::MultiPoint(positions) => {
-            GeoJsonGeomValue::MultiPoint(positions.iter().map(|pos| make_pt(pos, tr)).collect())
-        }
-        _ => unreachable!(),
-    };
-    Ok(Feature {
+fn make_feature(geom: &Geometry, value: GeoJsonGeomValue) -> Feature {
+    Feature {
         bbox: geom.bbox.clone(),
         foreign_members: geom.foreign_members.clone(),
         geometry: Some(GeoJsonGeometry {
             bbox: None,
             foreign_members: None,
-            value: geoj_geom_value,
+            value,
         }),
-        id: make_feature_id(geom.id.clone()),
+        id: make_feature_id(&geom.id),
         properties: geom.properties.clone(),
-    })
+    }
 }
 
-pub fn make_ring(arcs: &[Arc], ixs: &[i32], tr: &Option<TransformParams>) -> Vec<Position> {
+pub fn convert_geom_coords(
+    geom: &Geometry,
+    tr: &Option<TransformParams>,
+) -> Result<Feature, Error> {
+    match &geom.value {
+        TopoJsonGeomValue::Point(..) | TopoJsonGeomValue::MultiPoint(..) => Ok(make_feature(
+            geom,
+            geom_value_to_geojson(&geom.value, &[], tr)?,
+        )),
+        _ => unreachable!(),
+    }
+}
+
+/// Decode and concatenate the arcs referenced by `ixs`, failing with
+/// [`Error::ArcIndexOutOfBounds`] on an index referencing no arc.
+///
+/// Consecutive arcs share their join position, which is only kept once: like
+/// topojson-client, the last position of the line is replaced by the next arc.
+pub fn make_ring(
+    arcs: &[Arc],
+    ixs: &[i32],
+    tr: Option<TransformParams>,
+) -> Result<Vec<Position>, Error> {
     let mut result_line = Vec::with_capacity(ixs.len());
-    for _ix in ixs {
-        let ix;
-        let revert;
-        if *_ix < 0 {
-            revert = true;
-            ix = (_ix.abs() - 1) as usize;
-        } else {
-            revert = false;
-            ix = *_ix as usize;
-        }
-        let line_arc = &arcs[ix];
+    for &ix in ixs {
+        let arc_ref = ArcRef::from(ix);
+        let line_arc = arcs
+            .get(arc_ref.index())
+            .ok_or(Error::ArcIndexOutOfBounds(ix as i64))?;
         let mut line = decode_arc(line_arc, tr);
-        if revert {
+        if arc_ref.is_reversed() {
             line.reverse();
         }
+        if !result_line.is_empty() {
+            result_line.pop();
+        }
         result_line.append(&mut line);
     }
-    result_line
+    Ok(result_line)
 }
 
 pub fn convert_geom_arcs(
@@ -116,21 +147,47 @@ pub fn convert_geom_arcs(
     arcs: &[Arc],
     tr: &Option<TransformParams>,
 ) -> Result<Feature, Error> {
-    let geom_value = match &geom.value {
+    match &geom.value {
+        TopoJsonGeomValue::LineString(..)
+        | TopoJsonGeomValue::MultiLineString(..)
+        | TopoJsonGeomValue::Polygon(..)
+        | TopoJsonGeomValue::MultiPolygon(..) => Ok(make_feature(
+            geom,
+            geom_value_to_geojson(&geom.value, arcs, tr)?,
+        )),
+        _ => unreachable!(),
+    }
+}
+
+/// Convert the value of a TopoJSON geometry to a GeoJSON geometry value,
+/// decoding its arcs from `arcs` and applying the transform `tr` (if any).
+///
+/// Only the geometry is converted, so that it can be used to assemble custom
+/// features. Geometry collections give a GeoJSON geometry collection.
+pub fn geom_value_to_geojson(
+    value: &TopoJsonGeomValue,
+    arcs: &[Arc],
+    tr: &Option<TransformParams>,
+) -> Result<GeoJsonGeomValue, Error> {
+    let geom_value = match value {
+        TopoJsonGeomValue::Point(ref pos) => GeoJsonGeomValue::Point(make_pt(pos, *tr)),
+        TopoJsonGeomValue::MultiPoint(positions) => {
+            GeoJsonGeomValue::MultiPoint(positions.iter().map(|pos| make_pt(pos, *tr)).collect())
+        }
         TopoJsonGeomValue::LineString(ref arc_indexes) => {
-            GeoJsonGeomValue::LineString(make_ring(arcs, arc_indexes, tr))
+            GeoJsonGeomValue::LineString(make_ring(arcs, arc_indexes, *tr)?)
         }
         TopoJsonGeomValue::MultiLineString(arc_indexes) => GeoJsonGeomValue::MultiLineString(
             arc_indexes
                 .iter()
-                .map(|ixs| make_ring(arcs, ixs, tr))
-                .collect(),
+                .map(|ixs| make_ring(arcs, ixs, *tr))
+                .collect::<Result<_, _>>()?,
         ),
         TopoJsonGeomValue::Polygon(arc_indexes) => GeoJsonGeomValue::Polygon(
             arc_indexes
                 .iter()
-                .map(|ixs| make_ring(arcs, ixs, tr))
-                .collect(),
+                .map(|ixs| make_ring(arcs, ixs, *tr))
+                .collect::<Result<_, _>>()?,
         ),
         TopoJsonGeomValue::MultiPolygon(arcs_indexes) => {
             let mut polygons = Vec::with_capacity(arcs_indexes.len());
@@ -138,86 +195,643 @@ pub fn convert_geom_arcs(
                 polygons.push(
                     _arc_indexes_poly
                         .iter()
-                        .map(|ixs| make_ring(arcs, ixs, tr))
-                        .collect(),
+                        .map(|ixs| make_ring(arcs, ixs, *tr))
+                        .collect::<Result<_, _>>()?,
                 );
             }
             GeoJsonGeomValue::MultiPolygon(polygons)
         }
-        _ => unreachable!(),
+        TopoJsonGeomValue::GeometryCollection(geoms) => {
+            let mut geometries = Vec::with_capacity(geoms.len());
+            for g in geoms {
+                geometries.push(GeoJsonGeometry::new(geom_value_to_geojson(
+                    &g.value, arcs, tr,
+                )?));
+            }
+            GeoJsonGeomValue::GeometryCollection(geometries)
+        }
+        TopoJsonGeomValue::Unknown(..) => return Err(Error::GeometryUnknownType),
     };
-    Ok(Feature {
-        geometry: Some(GeoJsonGeometry {
-            value: geom_value,
-            bbox: None,
-            foreign_members: None,
-        }),
-        bbox: geom.bbox.clone(),
-        foreign_members: geom.foreign_members.clone(),
-        id: make_feature_id(geom.id.clone()),
-        properties: geom.properties.clone(),
-    })
+    Ok(geom_value)
 }
 
-pub fn convert_geometry_collection(
+/// Convert a TopoJSON geometry to GeoJSON features.
+///
+/// Geometry collections are converted recursively to the features of their members
+/// (so that nested collections are flattened), geometries of unknown type are skipped
+/// and any other geometry gives a single feature.
+pub fn convert_one_geometry(
     geom: &Geometry,
     arcs: &[Arc],
     tr: &Option<TransformParams>,
 ) -> Result<Vec<Feature>, Error> {
-    let features = match geom.value {
+    match &geom.value {
+        TopoJsonGeomValue::Point(..) | TopoJsonGeomValue::MultiPoint(..) => {
+            Ok(vec![convert_geom_coords(geom, tr)?])
+        }
+        TopoJsonGeomValue::LineString(..)
+        | TopoJsonGeomValue::MultiLineString(..)
+        | TopoJsonGeomValue::Polygon(..)
+        | TopoJsonGeomValue::MultiPolygon(..) => Ok(vec![convert_geom_arcs(geom, arcs, tr)?]),
         TopoJsonGeomValue::GeometryCollection(ref geoms) => {
             let mut features = Vec::with_capacity(geoms.len());
             for g in geoms.iter() {
-                match &g.value {
-                    TopoJsonGeomValue::Point(..) | TopoJsonGeomValue::MultiPoint(..) => {
-                        features.push(convert_geom_coords(g, tr)?);
-                    }
-                    TopoJsonGeomValue::LineString(..)
-                    | TopoJsonGeomValue::MultiLineString(..)
-                    | TopoJsonGeomValue::Polygon(..)
-                    | TopoJsonGeomValue::MultiPolygon(..) => {
-                        features.push(convert_geom_arcs(g, arcs, tr)?);
-                    }
-                    // According to https://github.com/topojson/topojson-client#feature
-                    // a geometry collection of geometry collections should be mapped to
-                    // a feature collection of features, each with a geometry collection.
-                    _ => unimplemented!(),
-                }
+                features.append(&mut convert_one_geometry(g, arcs, tr)?);
             }
-            features
+            Ok(features)
         }
-        _ => unreachable!(),
+        TopoJsonGeomValue::Unknown(..) => Ok(vec![]),
+    }
+}
+
+/// Options for [`to_geojson_with_options`].
+///
+/// All the options are disabled by default, which gives the same output than [`to_geojson`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ToGeoJsonOptions {
+    /// Add an `"_invalid": true` foreign member to the features having
+    /// a polygon ring which intersects itself.
+    pub report_invalid_rings: bool,
+    /// Set the bbox of each feature to the extent of its decoded coordinates,
+    /// replacing the bbox copied from the TopoJSON geometry.
+    pub recompute_feature_bbox: bool,
+    /// Convert the polygons having several exterior rings (detected by containment,
+    /// whatever the winding order) to multipolygons, grouping each exterior ring
+    /// with the holes it contains.
+    pub split_multi_outer_polygons: bool,
+    /// In multipolygons, give each hole to the smallest exterior ring containing it
+    /// rather than trusting the grouping of the TopoJSON geometry.
+    pub reassign_holes_by_containment: bool,
+    /// Swap the first two components of every position (and of the feature bbox),
+    /// for data written in latitude / longitude order.
+    pub swap_xy: bool,
+    /// When the object gives a single feature without id (it isn't a geometry collection,
+    /// or `collection_as_single_feature` is set), use the name of the object as its id.
+    pub use_object_name_as_id: bool,
+    /// Order the features by the Morton code of their centroid (computed on a grid
+    /// spanning the centroids of all features), for a better spatial locality of
+    /// the output. The sort is stable, and the features without position come last.
+    pub spatial_sort: bool,
+    /// Rebuild the properties of each feature with their keys in sorted order, for
+    /// an output which doesn't depend on the `preserve_order` feature of serde_json.
+    pub sort_property_keys: bool,
+    /// Add an `"_arcs"` foreign member holding the arc indexes of the TopoJSON
+    /// geometry to the features made of arcs, to inspect the topology connectivity.
+    pub include_arc_debug: bool,
+    /// Append its first position to the end of every polygon ring whose endpoints
+    /// differ, to repair the rings left open by malformed arcs.
+    pub close_rings: bool,
+    /// Convert a geometry collection object to a single feature, whose geometry is a
+    /// GeoJSON geometry collection and whose properties are those of the collection,
+    /// rather than to a feature per member. The collection mustn't contain geometries
+    /// of unknown type, and `include_arc_debug` is ignored for it.
+    pub collection_as_single_feature: bool,
+    /// Leave out the features whose geometry has no position, such as an empty
+    /// MultiPoint, a Polygon without rings or an empty geometry collection.
+    pub omit_empty_geometries: bool,
+}
+
+fn has_self_intersecting_ring(value: &GeoJsonGeomValue) -> bool {
+    match value {
+        GeoJsonGeomValue::Polygon(rings) => {
+            rings.iter().any(|r| algorithm::ring_self_intersects(r))
+        }
+        GeoJsonGeomValue::MultiPolygon(polygons) => polygons
+            .iter()
+            .flatten()
+            .any(|r| algorithm::ring_self_intersects(r)),
+        GeoJsonGeomValue::GeometryCollection(geoms) => {
+            geoms.iter().any(|g| has_self_intersecting_ring(&g.value))
+        }
+        _ => false,
+    }
+}
+
+fn split_multi_outer_polygons(value: &mut GeoJsonGeomValue) {
+    match value {
+        GeoJsonGeomValue::Polygon(rings) if rings.len() > 1 => {
+            let mut polygons = algorithm::group_rings(std::mem::take(rings));
+            *value = if polygons.len() == 1 {
+                GeoJsonGeomValue::Polygon(polygons.pop().unwrap())
+            } else {
+                GeoJsonGeomValue::MultiPolygon(polygons)
+            };
+        }
+        GeoJsonGeomValue::MultiPolygon(polygons) => {
+            *polygons = std::mem::take(polygons)
+                .into_iter()
+                .flat_map(|rings| match rings.len() {
+                    0 | 1 => vec![rings],
+                    _ => algorithm::group_rings(rings),
+                })
+                .collect();
+        }
+        GeoJsonGeomValue::GeometryCollection(geoms) => geoms
+            .iter_mut()
+            .for_each(|g| split_multi_outer_polygons(&mut g.value)),
+        _ => {}
+    }
+}
+
+fn is_empty_geometry(value: &GeoJsonGeomValue) -> bool {
+    match value {
+        GeoJsonGeomValue::Point(pos) => pos.is_empty(),
+        GeoJsonGeomValue::MultiPoint(positions) | GeoJsonGeomValue::LineString(positions) => {
+            positions.is_empty()
+        }
+        GeoJsonGeomValue::MultiLineString(lines) | GeoJsonGeomValue::Polygon(lines) => {
+            lines.iter().all(|line| line.is_empty())
+        }
+        GeoJsonGeomValue::MultiPolygon(polygons) => polygons.iter().flatten().all(|r| r.is_empty()),
+        GeoJsonGeomValue::GeometryCollection(geoms) => {
+            geoms.iter().all(|g| is_empty_geometry(&g.value))
+        }
+    }
+}
+
+fn close_rings(value: &mut GeoJsonGeomValue) {
+    fn close_ring(ring: &mut Vec<Position>) {
+        if ring.first() != ring.last() {
+            let first = ring[0].clone();
+            ring.push(first);
+        }
+    }
+    match value {
+        GeoJsonGeomValue::Polygon(rings) => rings.iter_mut().for_each(close_ring),
+        GeoJsonGeomValue::MultiPolygon(polygons) => {
+            polygons.iter_mut().flatten().for_each(close_ring)
+        }
+        GeoJsonGeomValue::GeometryCollection(geoms) => {
+            geoms.iter_mut().for_each(|g| close_rings(&mut g.value))
+        }
+        _ => {}
+    }
+}
+
+fn reassign_holes_by_containment(value: &mut GeoJsonGeomValue) {
+    match value {
+        GeoJsonGeomValue::MultiPolygon(polygons) => {
+            *polygons = algorithm::reassign_holes(std::mem::take(polygons));
+        }
+        GeoJsonGeomValue::GeometryCollection(geoms) => geoms
+            .iter_mut()
+            .for_each(|g| reassign_holes_by_containment(&mut g.value)),
+        _ => {}
+    }
+}
+
+fn spatial_sort(features: &mut Vec<Feature>) {
+    let centroids: Vec<Option<[f64; 2]>> = features
+        .iter()
+        .map(|f| {
+            f.geometry
+                .as_ref()
+                .and_then(|g| algorithm::geom_value_centroid(&g.value))
+        })
+        .collect();
+    let mut extent = None;
+    centroids
+        .iter()
+        .flatten()
+        .for_each(|c| algorithm::extend_bbox(&mut extent, c));
+    let extent = match extent {
+        Some(e) => e,
+        None => return,
     };
-    Ok(features)
+    let to_cell = |v: f64, min: f64, max: f64| {
+        if max > min {
+            ((v - min) / (max - min) * u16::MAX as f64).round() as u16
+        } else {
+            0
+        }
+    };
+    let mut keyed: Vec<(Option<u32>, Feature)> = centroids
+        .into_iter()
+        .map(|c| {
+            c.map(|[x, y]| {
+                algorithm::morton_code(
+                    to_cell(x, extent[0], extent[2]),
+                    to_cell(y, extent[1], extent[3]),
+                )
+            })
+        })
+        .zip(features.drain(..))
+        .collect();
+    // `None` is lower than any code, so compare the absence of centroid first:
+    keyed.sort_by_key(|(code, _)| (code.is_none(), code.unwrap_or(0)));
+    features.extend(keyed.into_iter().map(|(_, f)| f));
+}
+
+fn clip_polygon(rings: &[Vec<Position>], rect: &[f64; 4]) -> Option<Vec<Vec<Position>>> {
+    let mut clipped = rings.iter().map(|ring| algorithm::clip_ring(ring, rect));
+    // No polygon is left when its exterior ring is outside:
+    let exterior = clipped.next().filter(|ring| !ring.is_empty())?;
+    Some(
+        std::iter::once(exterior)
+            .chain(clipped.filter(|ring| !ring.is_empty()))
+            .collect(),
+    )
+}
+
+/// Clip a GeoJSON geometry value to the rectangle `rect`, `None` if nothing is left.
+fn clip_geom_value(value: &GeoJsonGeomValue, rect: &[f64; 4]) -> Option<GeoJsonGeomValue> {
+    fn non_empty<T>(v: Vec<T>) -> Option<Vec<T>> {
+        if v.is_empty() {
+            None
+        } else {
+            Some(v)
+        }
+    }
+    // Positions with less than two components are ignored by extents, and are
+    // left out of the clip as well:
+    let inside = |pos: &Position| match **pos {
+        [x, y, ..] => x >= rect[0] && x <= rect[2] && y >= rect[1] && y <= rect[3],
+        _ => false,
+    };
+    Some(match value {
+        GeoJsonGeomValue::Point(pos) if inside(pos) => GeoJsonGeomValue::Point(pos.clone()),
+        GeoJsonGeomValue::Point(..) => return None,
+        GeoJsonGeomValue::MultiPoint(positions) => GeoJsonGeomValue::MultiPoint(non_empty(
+            positions
+                .iter()
+                .filter(|pos| inside(pos))
+                .cloned()
+                .collect(),
+        )?),
+        GeoJsonGeomValue::LineString(line) => {
+            let mut parts = non_empty(algorithm::clip_line(line, rect))?;
+            if parts.len() == 1 {
+                GeoJsonGeomValue::LineString(parts.pop().unwrap())
+            } else {
+                GeoJsonGeomValue::MultiLineString(parts)
+            }
+        }
+        GeoJsonGeomValue::MultiLineString(lines) => GeoJsonGeomValue::MultiLineString(non_empty(
+            lines
+                .iter()
+                .flat_map(|line| algorithm::clip_line(line, rect))
+                .collect(),
+        )?),
+        GeoJsonGeomValue::Polygon(rings) => GeoJsonGeomValue::Polygon(clip_polygon(rings, rect)?),
+        GeoJsonGeomValue::MultiPolygon(polygons) => GeoJsonGeomValue::MultiPolygon(non_empty(
+            polygons
+                .iter()
+                .filter_map(|rings| clip_polygon(rings, rect))
+                .collect(),
+        )?),
+        GeoJsonGeomValue::GeometryCollection(geoms) => {
+            GeoJsonGeomValue::GeometryCollection(non_empty(
+                geoms
+                    .iter()
+                    .filter_map(|g| clip_geom_value(&g.value, rect))
+                    .map(GeoJsonGeometry::new)
+                    .collect(),
+            )?)
+        }
+    })
+}
+
+fn apply_options(feature: &mut Feature, options: &ToGeoJsonOptions) {
+    if options.swap_xy {
+        if let Some(ref mut geometry) = feature.geometry {
+            algorithm::for_each_geojson_position_mut(&mut geometry.value, &mut |pos| {
+                if pos.len() >= 2 {
+                    pos.swap(0, 1);
+                }
+            });
+        }
+        if let Some(ref mut bbox) = feature.bbox {
+            if bbox.len() >= 4 {
+                let half = bbox.len() / 2;
+                bbox.swap(0, 1);
+                bbox.swap(half, half + 1);
+            }
+        }
+    }
+    if options.close_rings {
+        if let Some(ref mut geometry) = feature.geometry {
+            close_rings(&mut geometry.value);
+        }
+    }
+    if options.split_multi_outer_polygons {
+        if let Some(ref mut geometry) = feature.geometry {
+            split_multi_outer_polygons(&mut geometry.value);
+        }
+    }
+    if options.reassign_holes_by_containment {
+        if let Some(ref mut geometry) = feature.geometry {
+            reassign_holes_by_containment(&mut geometry.value);
+        }
+    }
+    if options.recompute_feature_bbox {
+        feature.bbox = feature
+            .geometry
+            .as_ref()
+            .and_then(|geometry| algorithm::geom_value_extent(&geometry.value))
+            .map(|extent| extent.to_vec());
+    }
+    if options.sort_property_keys {
+        if let Some(properties) = feature.properties.take() {
+            let mut entries: Vec<(String, JsonValue)> = properties.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            feature.properties = Some(entries.into_iter().collect());
+        }
+    }
+    if options.report_invalid_rings {
+        let invalid = match feature.geometry {
+            Some(ref geometry) => has_self_intersecting_ring(&geometry.value),
+            None => false,
+        };
+        if invalid {
+            feature
+                .foreign_members
+                .get_or_insert_with(JsonObject::new)
+                .insert(String::from("_invalid"), JsonValue::Bool(true));
+        }
+    }
 }
 
 /// Convert a TopoJSON Topology object to a GeoJSON Feature collection.
 ///
 /// (in a similar way than [topojson.feature](https://github.com/topojson/topojson-client#feature) function
 /// or [topo2geo](https://github.com/topojson/topojson-client#topo2geo) CLI tool)
+///
+/// The features are in the order of the geometries of the object, the members of
+/// nested geometry collections taking the place of their collection.
 pub fn to_geojson(topo: &Topology, key: &str) -> Result<FeatureCollection, Error> {
+    to_geojson_with_options(topo, key, &ToGeoJsonOptions::default())
+}
+
+/// Convert a TopoJSON Topology object to a GeoJSON Feature collection,
+/// post-processing the features according to `options`.
+pub fn to_geojson_with_options(
+    topo: &Topology,
+    key: &str,
+    options: &ToGeoJsonOptions,
+) -> Result<FeatureCollection, Error> {
     let objs: Vec<&NamedGeometry> = topo.objects.iter().filter(|ng| ng.name == key).collect();
-    let features = match objs.len() {
-        0 => return Err(Error::TopoToGeoUnknownKey(key.to_owned())),
-        1 => match &objs[0].geometry.value {
-            TopoJsonGeomValue::Point(..) | TopoJsonGeomValue::MultiPoint(..) => {
-                vec![convert_geom_coords(&objs[0].geometry, &topo.transform)?]
+    if objs.is_empty() {
+        return Err(Error::TopoToGeoUnknownKey(key.to_owned()));
+    }
+    let geometry = &objs[0].geometry;
+    let is_collection = matches!(geometry.value, TopoJsonGeomValue::GeometryCollection(..));
+    let single_feature = options.collection_as_single_feature && is_collection;
+    let mut features = if single_feature {
+        vec![make_feature(
+            geometry,
+            geom_value_to_geojson(&geometry.value, &topo.arcs, &topo.transform)?,
+        )]
+    } else {
+        convert_one_geometry(geometry, &topo.arcs, &topo.transform)?
+    };
+    for feature in features.iter_mut() {
+        apply_options(feature, options);
+    }
+    if options.include_arc_debug && !single_feature {
+        // The features are the geometries which aren't collections (nor of unknown
+        // type), in the order they are visited:
+        let mut arcs = Vec::with_capacity(features.len());
+        geometry.for_each_geometry(&mut |g| match g.value {
+            TopoJsonGeomValue::GeometryCollection(..) | TopoJsonGeomValue::Unknown(..) => {}
+            TopoJsonGeomValue::Point(..) | TopoJsonGeomValue::MultiPoint(..) => arcs.push(None),
+            _ => arcs.push(Some(g.value.to_json_value())),
+        });
+        for (feature, arcs) in features.iter_mut().zip(arcs) {
+            if let Some(arcs) = arcs {
+                feature
+                    .foreign_members
+                    .get_or_insert_with(JsonObject::new)
+                    .insert(String::from("_arcs"), arcs);
+            }
+        }
+    }
+    if options.omit_empty_geometries {
+        features.retain(|f| {
+            !f.geometry
+                .as_ref()
+                .is_some_and(|geometry| is_empty_geometry(&geometry.value))
+        });
+    }
+    if options.spatial_sort {
+        spatial_sort(&mut features);
+    }
+    if options.use_object_name_as_id && (single_feature || !is_collection) {
+        if let Some(feature) = features.first_mut() {
+            feature
+                .id
+                .get_or_insert_with(|| FeatureId::String(key.to_owned()));
+        }
+    }
+
+    Ok(FeatureCollection {
+        features,
+        bbox: None,
+        foreign_members: None,
+    })
+}
+
+/// Convert a TopoJSON Topology object to a GeoJSON Feature collection, calling
+/// `reproject` on every position of the output (after the arcs are decoded and the
+/// transform is applied).
+///
+/// The features are converted independently, so the points of an arc shared by
+/// several features (or used twice by the same one) are reprojected each time they
+/// appear in the output. The feature bboxes copied from the topology are left as is.
+pub fn to_geojson_with_reproject<F: Fn(&mut Position)>(
+    topo: &Topology,
+    key: &str,
+    reproject: F,
+) -> Result<FeatureCollection, Error> {
+    let mut fc = to_geojson(topo, key)?;
+    for feature in fc.features.iter_mut() {
+        if let Some(ref mut geometry) = feature.geometry {
+            algorithm::for_each_geojson_position_mut(&mut geometry.value, &mut |pos| {
+                reproject(pos)
+            });
+        }
+    }
+    Ok(fc)
+}
+
+/// Convert a TopoJSON Topology object to a GeoJSON Feature collection,
+/// keeping only the geometries for which `keep` returns `true`.
+///
+/// When the object is a geometry collection the predicate is called on each of
+/// its members, otherwise on the object geometry itself. Rejected geometries are
+/// never decoded.
+pub fn to_geojson_filtered<F: Fn(&Geometry) -> bool>(
+    topo: &Topology,
+    key: &str,
+    keep: F,
+) -> Result<FeatureCollection, Error> {
+    let obj = topo
+        .objects
+        .iter()
+        .find(|ng| ng.name == key)
+        .ok_or_else(|| Error::TopoToGeoUnknownKey(key.to_owned()))?;
+
+    let geometries = match &obj.geometry.value {
+        TopoJsonGeomValue::GeometryCollection(ref geoms) => geoms.iter().collect(),
+        _ => vec![&obj.geometry],
+    };
+    let mut features = Vec::new();
+    for g in geometries.into_iter().filter(|g| keep(g)) {
+        features.append(&mut convert_one_geometry(g, &topo.arcs, &topo.transform)?);
+    }
+
+    Ok(FeatureCollection {
+        features,
+        bbox: None,
+        foreign_members: None,
+    })
+}
+
+/// Convert a TopoJSON Topology object to a GeoJSON Feature collection, skipping
+/// the geometries which fail to decode (e.g. on an out-of-bounds arc index)
+/// instead of failing the whole conversion.
+///
+/// The geometries are the members of the object if it is a geometry collection,
+/// otherwise the object geometry itself: the errors are returned along with the
+/// position of their geometry. An unknown `key` gives an empty collection and a
+/// [`Error::TopoToGeoUnknownKey`] at position 0.
+pub fn to_geojson_lenient(topo: &Topology, key: &str) -> (FeatureCollection, Vec<(usize, Error)>) {
+    let mut features = Vec::new();
+    let mut errors = Vec::new();
+    match topo.objects.iter().find(|ng| ng.name == key) {
+        None => errors.push((0, Error::TopoToGeoUnknownKey(key.to_owned()))),
+        Some(obj) => {
+            let geometries = match &obj.geometry.value {
+                TopoJsonGeomValue::GeometryCollection(ref geoms) => geoms.iter().collect(),
+                _ => vec![&obj.geometry],
+            };
+            for (i, g) in geometries.into_iter().enumerate() {
+                match convert_one_geometry(g, &topo.arcs, &topo.transform) {
+                    Ok(mut converted) => features.append(&mut converted),
+                    Err(e) => errors.push((i, e)),
+                }
             }
-            TopoJsonGeomValue::LineString(..)
-            | TopoJsonGeomValue::MultiLineString(..)
-            | TopoJsonGeomValue::Polygon(..)
-            | TopoJsonGeomValue::MultiPolygon(..) => vec![convert_geom_arcs(
-                &objs[0].geometry,
-                &topo.arcs,
-                &topo.transform,
-            )?],
-            TopoJsonGeomValue::GeometryCollection(..) => {
-                convert_geometry_collection(&objs[0].geometry, &topo.arcs, &topo.transform)?
+        }
+    }
+    let collection = FeatureCollection {
+        features,
+        bbox: None,
+        foreign_members: None,
+    };
+    (collection, errors)
+}
+
+/// Convert a TopoJSON Topology object to a GeoJSON Feature collection,
+/// keeping only the features whose extent intersects `bbox`
+/// (given as `[minx, miny, maxx, maxy]` in real-world coordinates).
+///
+/// This is a coarse filter on the extents, the geometries are not clipped.
+/// The arcs referenced by the object are decoded once to compute their extents,
+/// then the features outside of `bbox` are rejected without being converted.
+/// See [`to_geojson_in_bbox_with_arc_bboxes`] to reuse the extents across calls.
+pub fn to_geojson_in_bbox(
+    topo: &Topology,
+    key: &str,
+    bbox: [f64; 4],
+) -> Result<FeatureCollection, Error> {
+    let obj = topo
+        .objects
+        .iter()
+        .find(|ng| ng.name == key)
+        .ok_or_else(|| Error::TopoToGeoUnknownKey(key.to_owned()))?;
+    let arc_bboxes = topo.arc_bboxes_of(&obj.geometry);
+    to_geojson_in_bbox_with_arc_bboxes(topo, key, bbox, &arc_bboxes)
+}
+
+/// Like [`to_geojson_in_bbox`], using the precomputed extents of the arcs
+/// (`arc_bboxes`, as returned by [`Topology::arc_bboxes`]) so that no arc is decoded
+/// to filter the features.
+pub fn to_geojson_in_bbox_with_arc_bboxes(
+    topo: &Topology,
+    key: &str,
+    bbox: [f64; 4],
+    arc_bboxes: &[[f64; 4]],
+) -> Result<FeatureCollection, Error> {
+    to_geojson_filtered(topo, key, |g| {
+        topo.geometry_extent(g, arc_bboxes)
+            .is_some_and(|e| algorithm::bboxes_intersect(&e, &bbox))
+    })
+}
+
+/// Convert a TopoJSON Topology object to a GeoJSON Feature collection, clipping
+/// its geometries to the rectangle `clip` (given as `[minx, miny, maxx, maxy]` in
+/// real-world coordinates).
+///
+/// Lines are clipped with the Liang–Barsky algorithm (a line crossing the rectangle
+/// several times becoming a MultiLineString) and polygon rings with the
+/// Sutherland–Hodgman algorithm. The features left without geometry are dropped,
+/// and the bbox of the others (if any) is set to the extent of their clipped geometry.
+pub fn to_geojson_clipped(
+    topo: &Topology,
+    key: &str,
+    clip: [f64; 4],
+) -> Result<FeatureCollection, Error> {
+    let mut fc = to_geojson_in_bbox(topo, key, clip)?;
+    fc.features = fc
+        .features
+        .into_iter()
+        .filter_map(|mut feature| {
+            let geometry = feature.geometry.as_mut()?;
+            geometry.value = clip_geom_value(&geometry.value, &clip)?;
+            if feature.bbox.is_some() {
+                feature.bbox = algorithm::geom_value_extent(&geometry.value).map(|e| e.to_vec());
             }
-        },
-        _ => unreachable!(),
+            Some(feature)
+        })
+        .collect();
+    Ok(fc)
+}
+
+/// Convert a TopoJSON Topology object to a GeoJSON Feature collection,
+/// decoding the members of a geometry collection on several threads.
+///
+/// The arcs and the transform are shared by all the threads, and the features
+/// are returned in the same order as [`to_geojson`].
+///
+/// The members are split in one chunk per available core, each converted on a
+/// scoped `std` thread (there is no thread pool, so the `parallel` feature doesn't
+/// pull any dependency). A panicking thread gives [`Error::ConversionThreadPanicked`].
+#[cfg(feature = "parallel")]
+pub fn to_geojson_parallel(topo: &Topology, key: &str) -> Result<FeatureCollection, Error> {
+    let obj = topo
+        .objects
+        .iter()
+        .find(|ng| ng.name == key)
+        .ok_or_else(|| Error::TopoToGeoUnknownKey(key.to_owned()))?;
+    let geoms: &[Geometry] = match &obj.geometry.value {
+        TopoJsonGeomValue::GeometryCollection(ref geoms) => geoms,
+        _ => std::slice::from_ref(&obj.geometry),
     };
 
+    let n_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = geoms.len().div_ceil(n_threads).max(1);
+    let chunks: Vec<Result<Vec<Feature>, Error>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = geoms
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut features = Vec::with_capacity(chunk.len());
+                    for g in chunk {
+                        features.append(&mut convert_one_geometry(g, &topo.arcs, &topo.transform)?);
+                    }
+                    Ok(features)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap_or(Err(Error::ConversionThreadPanicked)))
+            .collect()
+    });
+
+    let mut features = Vec::with_capacity(geoms.len());
+    for chunk in chunks {
+        features.append(&mut chunk?);
+    }
     Ok(FeatureCollection {
         features,
         bbox: None,
@@ -225,9 +839,163 @@ pub fn to_geojson(topo: &Topology, key: &str) -> Result<FeatureCollection, Error
     })
 }
 
+/// Convert every object of a TopoJSON Topology to a GeoJSON Feature collection,
+/// returned with the object names in the order of `topo.objects`.
+pub fn to_geojson_all(topo: &Topology) -> Result<Vec<(String, FeatureCollection)>, Error> {
+    topo.objects
+        .iter()
+        .map(|ng| Ok((ng.name.clone(), to_geojson(topo, &ng.name)?)))
+        .collect()
+}
+
+/// Convert a TopoJSON Topology object which is a single geometry to a GeoJSON Feature.
+///
+/// Fails with [`Error::ObjectIsCollection`] if the object is a geometry collection
+/// (which [`to_geojson`] converts to several features).
+pub fn to_feature(topo: &Topology, key: &str) -> Result<Feature, Error> {
+    let obj = topo
+        .objects
+        .iter()
+        .find(|ng| ng.name == key)
+        .ok_or_else(|| Error::TopoToGeoUnknownKey(key.to_owned()))?;
+    match obj.geometry.value {
+        TopoJsonGeomValue::GeometryCollection(..) => Err(Error::ObjectIsCollection(key.to_owned())),
+        _ => convert_one_geometry(&obj.geometry, &topo.arcs, &topo.transform)?
+            .pop()
+            .ok_or(Error::GeometryUnknownType),
+    }
+}
+
+/// Convert the objects `keys` of a TopoJSON Topology to a single GeoJSON Feature
+/// collection, with the features of each object in the order of `keys`.
+///
+/// Fails with [`Error::TopoToGeoUnknownKey`] if any of the keys isn't in the topology.
+pub fn to_geojson_keys(topo: &Topology, keys: &[&str]) -> Result<FeatureCollection, Error> {
+    let mut features = Vec::new();
+    for key in keys {
+        let obj = topo
+            .objects
+            .iter()
+            .find(|ng| ng.name == *key)
+            .ok_or_else(|| Error::TopoToGeoUnknownKey((*key).to_owned()))?;
+        features.append(&mut convert_one_geometry(
+            &obj.geometry,
+            &topo.arcs,
+            &topo.transform,
+        )?);
+    }
+    Ok(FeatureCollection {
+        features,
+        bbox: None,
+        foreign_members: None,
+    })
+}
+
+/// Convert the geometries of the given TopoJSON `types` ('LineString', 'Polygon', etc.)
+/// across all the objects of a TopoJSON Topology to a single GeoJSON Feature collection,
+/// with the features in the order of `topo.objects`.
+///
+/// Geometry collections are descended into (their members are matched, not the
+/// collections themselves), and the geometries of other types are never decoded.
+pub fn to_geojson_by_type(topo: &Topology, types: &[&str]) -> Result<FeatureCollection, Error> {
+    fn collect<'a>(geom: &'a Geometry, types: &[&str], geometries: &mut Vec<&'a Geometry>) {
+        match geom.value {
+            TopoJsonGeomValue::GeometryCollection(ref geoms) => {
+                geoms.iter().for_each(|g| collect(g, types, geometries))
+            }
+            ref value if types.contains(&value.type_name()) => geometries.push(geom),
+            _ => {}
+        }
+    }
+
+    let mut geometries = Vec::new();
+    for ng in topo.objects.iter() {
+        collect(&ng.geometry, types, &mut geometries);
+    }
+    let mut features = Vec::new();
+    for g in geometries {
+        features.append(&mut convert_one_geometry(g, &topo.arcs, &topo.transform)?);
+    }
+    Ok(FeatureCollection {
+        features,
+        bbox: None,
+        foreign_members: None,
+    })
+}
+
+/// Convert every object of a TopoJSON Topology to GeoJSON, writing each of them to
+/// a `<name>.geojson` file in `dir` (which must exist). Returns the written paths.
+///
+/// Path separators in the object names are replaced by `_`. Fails with
+/// [`Error::DuplicateFileName`] (before writing any file) if two objects get the
+/// same file name this way, such as `a/b` and `a_b`.
+pub fn write_geojson_files(topo: &Topology, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut file_names = HashSet::with_capacity(topo.objects.len());
+    let mut paths = Vec::with_capacity(topo.objects.len());
+    for named_geom in topo.objects.iter() {
+        let file_name = named_geom.name.replace(['/', '\\'], "_") + ".geojson";
+        if !file_names.insert(file_name.clone()) {
+            return Err(Error::DuplicateFileName(file_name));
+        }
+        paths.push(dir.join(file_name));
+    }
+    for ((_, fc), path) in to_geojson_all(topo)?.into_iter().zip(paths.iter()) {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, &GeoJson::FeatureCollection(fc))
+            .map_err(|e| Error::from(std::io::Error::from(e)))?;
+        writer.flush()?;
+    }
+    Ok(paths)
+}
+
+fn write_feature<W: Write>(writer: &mut W, feature: &Feature) -> Result<(), Error> {
+    serde_json::to_writer(writer, feature).map_err(|e| Error::from(std::io::Error::from(e)))
+}
+
+/// Convert a TopoJSON Topology object to a GeoJSON Feature collection,
+/// writing it incrementally to `writer`.
+///
+/// The result is the same as serializing the output of [`to_geojson`], but features
+/// are decoded and written one at a time so that the whole collection is never
+/// held in memory.
+pub fn write_geojson<W: Write>(topo: &Topology, key: &str, mut writer: W) -> Result<(), Error> {
+    let obj = topo
+        .objects
+        .iter()
+        .find(|ng| ng.name == key)
+        .ok_or_else(|| Error::TopoToGeoUnknownKey(key.to_owned()))?;
+
+    writer.write_all(br#"{"type":"FeatureCollection","features":["#)?;
+    let geometries = match &obj.geometry.value {
+        TopoJsonGeomValue::GeometryCollection(ref geoms) => geoms.iter().collect(),
+        _ => vec![&obj.geometry],
+    };
+    let mut first = true;
+    for g in geometries {
+        for feature in convert_one_geometry(g, &topo.arcs, &topo.transform)? {
+            if !first {
+                writer.write_all(b",")?;
+            }
+            first = false;
+            write_feature(&mut writer, &feature)?;
+        }
+    }
+    writer.write_all(b"]}")?;
+    writer.flush()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{to_geojson, Error, TopoJson};
+    use super::{convert_geom_arcs, convert_geom_coords};
+    use crate::{geom_value_to_geojson, Value};
+    use crate::{
+        to_feature, to_geojson, to_geojson_all, to_geojson_by_type, to_geojson_clipped,
+        to_geojson_filtered, to_geojson_in_bbox, to_geojson_in_bbox_with_arc_bboxes,
+        to_geojson_keys, to_geojson_lenient, to_geojson_with_options, to_geojson_with_reproject,
+        write_geojson, Error, ToGeoJsonOptions, TopoJson,
+    };
+    use geojson::feature::Id as FeatureId;
     use geojson::GeoJson;
 
     fn decode(json_string: &str) -> TopoJson {
@@ -261,9 +1029,862 @@ mod tests {
         let geojson_string = GeoJson::FeatureCollection(geojson_obj).to_string();
 
         // The expected result was obtained using [topo2geo CLI tool](https://github.com/topojson/topojson-client#command-line-reference)
-        // (then parsed with rust to obtain the same field order / same rounding issues)
-        let expected_geojson_string = "{\"features\":[{\"geometry\":{\"coordinates\":[102.000200020002,0.5000500050005],\"type\":\"Point\"},\"properties\":{\"prop0\":\"value0\"},\"type\":\"Feature\"},{\"geometry\":{\"coordinates\":[[102.000200020002,0.0],[102.999799979998,0.9999999999999999],[103.999899989999,0.0],[105.0,0.9999999999999999]],\"type\":\"LineString\"},\"properties\":{\"prop0\":\"value0\",\"prop1\":0},\"type\":\"Feature\"},{\"geometry\":{\"coordinates\":[[[100.0,0.0],[100.0,0.9999999999999999],[101.000100010001,0.9999999999999999],[101.000100010001,0.0],[100.0,0.0]]],\"type\":\"Polygon\"},\"properties\":{\"prop0\":\"value0\",\"prop1\":{\"this\":\"that\"}},\"type\":\"Feature\"}],\"type\":\"FeatureCollection\"}";
+        // (then parsed with rust to obtain the same field order / same rounding issues,
+        // the field order being the one of geojson 0.24)
+        let expected_geojson_string = "{\"type\":\"FeatureCollection\",\"features\":[{\"type\":\"Feature\",\"geometry\":{\"type\":\"Point\",\"coordinates\":[102.000200020002,0.5000500050005]},\"properties\":{\"prop0\":\"value0\"}},{\"type\":\"Feature\",\"geometry\":{\"type\":\"LineString\",\"coordinates\":[[102.000200020002,0.0],[102.999799979998,0.9999999999999999],[103.999899989999,0.0],[105.0,0.9999999999999999]]},\"properties\":{\"prop0\":\"value0\",\"prop1\":0}},{\"type\":\"Feature\",\"geometry\":{\"type\":\"Polygon\",\"coordinates\":[[[100.0,0.0],[100.0,0.9999999999999999],[101.000100010001,0.9999999999999999],[101.000100010001,0.0],[100.0,0.0]]]},\"properties\":{\"prop0\":\"value0\",\"prop1\":{\"this\":\"that\"}}}]}";
 
+        #[cfg(not(feature = "arbitrary-precision"))]
         assert_eq!(geojson_string, expected_geojson_string);
+        // serde_json parses the transform with a different (exact) rounding
+        // when its arbitrary_precision feature is enabled:
+        #[cfg(feature = "arbitrary-precision")]
+        {
+            let features = |s: &str| match s.parse::<GeoJson>().unwrap() {
+                GeoJson::FeatureCollection(fc) => fc.features,
+                _ => unreachable!(),
+            };
+            let (result, expected) = (features(&geojson_string), features(expected_geojson_string));
+            assert_eq!(result.len(), expected.len());
+            for (f, g) in result.iter().zip(expected.iter()) {
+                assert_eq!(f.properties, g.properties);
+                assert!(crate::algorithm::geom_values_approx_eq(
+                    &f.geometry.as_ref().unwrap().value,
+                    &g.geometry.as_ref().unwrap().value,
+                    1e-12
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn write_geojson_matches_to_geojson() {
+        let topo = decode("{\"arcs\":[[[4000,0],[1999,9999],[2000,-9999],[2000,9999]],[[0,0],[0,9999],[2000,0],[0,-9999],[-2000,0]]],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[4000,5000],\"properties\":{\"prop0\":\"value0\"},\"type\":\"Point\"},{\"arcs\":[0],\"properties\":{\"prop0\":\"value0\",\"prop1\":0},\"type\":\"LineString\"},{\"arcs\":[[1]],\"properties\":{\"prop0\":\"value0\",\"prop1\":{\"this\":\"that\"}},\"type\":\"Polygon\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\",\"transform\":{\"scale\":[0.0005000500050005,0.00010001000100010001],\"translate\":[100,0]}}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write_geojson(&topo, "example", &mut buffer).expect("Unable to write GeoJson");
+        let written = String::from_utf8(buffer)
+            .unwrap()
+            .parse::<GeoJson>()
+            .expect("Invalid GeoJson written");
+
+        // The expected value also goes through a serialization round trip
+        // so that both sides are parsed with the same float rounding:
+        let expected = GeoJson::FeatureCollection(to_geojson(&topo, "example").unwrap())
+            .to_string()
+            .parse::<GeoJson>()
+            .unwrap();
+        assert_eq!(written, expected);
+    }
+
+    #[test]
+    fn write_geojson_single_geometry() {
+        let topo = decode("{\"arcs\":[[[2.2,2.2],[3.3,3.3]]],\"objects\":{\"example\":{\"arcs\":[0],\"type\":\"LineString\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write_geojson(&topo, "example", &mut buffer).unwrap();
+        match String::from_utf8(buffer).unwrap().parse::<GeoJson>() {
+            Ok(GeoJson::FeatureCollection(fc)) => assert_eq!(fc.features.len(), 1),
+            _ => panic!(),
+        }
+
+        let result = write_geojson(&topo, "foo", Vec::new());
+        assert_eq!(result, Err(Error::TopoToGeoUnknownKey("foo".to_string())));
+    }
+
+    #[test]
+    fn convert_points_only_topology_without_arcs() {
+        let topo = decode("{\"arcs\":[],\"objects\":{\"points\":{\"coordinates\":[[4000,5000],[2000,0]],\"type\":\"MultiPoint\"},\"collection\":{\"geometries\":[{\"coordinates\":[0,0],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"transform\":{\"scale\":[0.0005,0.0001],\"translate\":[100,0]},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        assert!(topo.arcs.is_empty());
+
+        let geojson_obj = to_geojson(&topo, "points").expect("Unable to convert to GeoJson");
+        assert_eq!(geojson_obj.features.len(), 1);
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::MultiPoint(vec![vec![102.0, 0.5], vec![101.0, 0.0]])
+        );
+
+        let geojson_obj = to_geojson(&topo, "collection").expect("Unable to convert to GeoJson");
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::Point(vec![100.0, 0.0])
+        );
+    }
+
+    #[test]
+    fn convert_stitches_arcs_without_repeating_joins() {
+        // A square made of two arcs, the second one being used reversed:
+        let topo = decode("{\"arcs\":[[[0,0],[1,0],[1,1]],[[0,0],[0,1],[1,1]]],\"objects\":{\"square\":{\"arcs\":[[0,-2]],\"type\":\"Polygon\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let geojson_obj = to_geojson(&topo, "square").unwrap();
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::Polygon(vec![vec![
+                vec![0., 0.],
+                vec![1., 0.],
+                vec![1., 1.],
+                vec![0., 1.],
+                vec![0., 0.],
+            ]])
+        );
+    }
+
+    #[test]
+    fn convert_sorts_property_keys() {
+        let topo = decode("{\"arcs\":[],\"objects\":{\"example\":{\"coordinates\":[0,0],\"properties\":{\"b\":2,\"a\":1,\"c\":3},\"type\":\"Point\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let options = ToGeoJsonOptions {
+            sort_property_keys: true,
+            ..Default::default()
+        };
+        let geojson_obj = to_geojson_with_options(&topo, "example", &options).unwrap();
+        let keys: Vec<&String> = geojson_obj.features[0]
+            .properties
+            .as_ref()
+            .unwrap()
+            .keys()
+            .collect();
+        assert_eq!(keys, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn convert_single_geometry_to_feature() {
+        let topo = decode("{\"arcs\":[[[0.0,0.0],[1.0,1.0]]],\"objects\":{\"line\":{\"arcs\":[0],\"id\":\"l1\",\"type\":\"LineString\"},\"collection\":{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let feature = to_feature(&topo, "line").unwrap();
+        assert_eq!(feature, to_geojson(&topo, "line").unwrap().features[0]);
+        assert_eq!(feature.id, Some(FeatureId::String("l1".into())));
+
+        assert_eq!(
+            to_feature(&topo, "collection"),
+            Err(Error::ObjectIsCollection(String::from("collection")))
+        );
+    }
+
+    #[test]
+    fn convert_clipped() {
+        let topo = decode("{\"arcs\":[[[-1.0,1.0],[3.0,1.0]],[[1.0,1.0],[3.0,1.0],[3.0,3.0],[1.0,3.0],[1.0,1.0]],[[5.0,5.0],[6.0,6.0]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"},{\"arcs\":[[1]],\"bbox\":[1.0,1.0,3.0,3.0],\"type\":\"Polygon\"},{\"arcs\":[2],\"type\":\"LineString\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let geojson_obj = to_geojson_clipped(&topo, "example", [0., 0., 2., 2.]).unwrap();
+        // The line outside of the rectangle is dropped:
+        assert_eq!(geojson_obj.features.len(), 2);
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::LineString(vec![vec![0., 1.], vec![2., 1.]])
+        );
+        assert_eq!(
+            geojson_obj.features[1].geometry.as_ref().unwrap().value,
+            geojson::Value::Polygon(vec![vec![
+                vec![1., 2.],
+                vec![1., 1.],
+                vec![2., 1.],
+                vec![2., 2.],
+                vec![1., 2.],
+            ]])
+        );
+        assert_eq!(geojson_obj.features[1].bbox, Some(vec![1., 1., 2., 2.]));
+
+        // A position with less than two components is left out of the clip:
+        let topo = decode("{\"arcs\":[],\"objects\":{\"points\":{\"coordinates\":[[0,0],[1]],\"type\":\"MultiPoint\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let geojson_obj = to_geojson_clipped(&topo, "points", [-1., -1., 1., 1.]).unwrap();
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::MultiPoint(vec![vec![0., 0.]])
+        );
+    }
+
+    #[test]
+    fn convert_includes_arc_debug() {
+        let topo = decode("{\"arcs\":[[[0.0,0.0],[1.0,1.0]],[[1.0,1.0],[0.0,1.0],[0.0,0.0]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"},{\"coordinates\":[0.0,0.0],\"type\":\"Point\"},{\"arcs\":[[0,1]],\"type\":\"Polygon\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let options = ToGeoJsonOptions {
+            include_arc_debug: true,
+            ..Default::default()
+        };
+        let geojson_obj = to_geojson_with_options(&topo, "example", &options).unwrap();
+        let arcs: Vec<_> = geojson_obj
+            .features
+            .iter()
+            .map(|f| f.foreign_members.as_ref().and_then(|fm| fm.get("_arcs")))
+            .collect();
+        assert_eq!(arcs, vec![Some(&json!([0])), None, Some(&json!([[0, 1]]))]);
+    }
+
+    #[test]
+    fn convert_empty_inputs() {
+        let topo = decode("{\"arcs\":[],\"objects\":{\"empty\":{\"geometries\":[],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        assert!(to_geojson(&topo, "empty").unwrap().features.is_empty());
+        assert!(to_geojson_keys(&topo, &["empty"])
+            .unwrap()
+            .features
+            .is_empty());
+
+        let topo = decode("{\"arcs\":[],\"objects\":{},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        assert!(to_geojson_all(&topo).unwrap().is_empty());
+    }
+
+    #[test]
+    fn convert_reports_invalid_rings() {
+        // A bowtie polygon and a square:
+        let topo = decode("{\"arcs\":[[[0,0],[2,2],[2,0],[0,2],[0,0]],[[5,5],[6,5],[6,6],[5,6],[5,5]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[[0]],\"type\":\"Polygon\"},{\"arcs\":[[[1]]],\"type\":\"MultiPolygon\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let options = ToGeoJsonOptions {
+            report_invalid_rings: true,
+            ..Default::default()
+        };
+        let geojson_obj = to_geojson_with_options(&topo, "example", &options).unwrap();
+        let invalid: Vec<bool> = geojson_obj
+            .features
+            .iter()
+            .map(|f| {
+                f.foreign_members
+                    .as_ref()
+                    .and_then(|fm| fm.get("_invalid"))
+                    .is_some()
+            })
+            .collect();
+        assert_eq!(invalid, vec![true, false]);
+
+        // Nothing is reported without the option:
+        let geojson_obj = to_geojson(&topo, "example").unwrap();
+        assert!(geojson_obj
+            .features
+            .iter()
+            .all(|f| f.foreign_members.is_none()));
+    }
+
+    #[test]
+    fn convert_filtered_by_property() {
+        let topo = decode("{\"arcs\":[[[0,0],[1,1]],[[1,1],[2,2]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"properties\":{\"pop\":10},\"type\":\"LineString\"},{\"arcs\":[1],\"properties\":{\"pop\":5000},\"type\":\"LineString\"},{\"coordinates\":[3,3],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let geojson_obj = to_geojson_filtered(&topo, "example", |g| {
+            g.property("pop")
+                .and_then(|pop| pop.as_f64())
+                .is_some_and(|pop| pop > 1000.)
+        })
+        .unwrap();
+        assert_eq!(geojson_obj.features.len(), 1);
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::LineString(vec![vec![1., 1.], vec![2., 2.]])
+        );
+    }
+
+    #[test]
+    fn convert_in_bbox() {
+        let topo = decode("{\"arcs\":[[[0,0],[1,1]],[[10,10],[12,12]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"id\":\"inside\",\"type\":\"LineString\"},{\"arcs\":[-2],\"id\":\"outside\",\"type\":\"LineString\"},{\"coordinates\":[[5,5],[20,20]],\"id\":\"overlapping\",\"type\":\"MultiPoint\"},{\"coordinates\":[30,30],\"id\":\"far\",\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let geojson_obj = to_geojson_in_bbox(&topo, "example", [0.5, 0.5, 8., 8.]).unwrap();
+        let ids: Vec<_> = geojson_obj
+            .features
+            .iter()
+            .map(|f| f.id.clone().unwrap())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                geojson::feature::Id::String("inside".to_string()),
+                geojson::feature::Id::String("overlapping".to_string()),
+            ]
+        );
+        assert_eq!(
+            to_geojson_in_bbox_with_arc_bboxes(
+                &topo,
+                "example",
+                [0.5, 0.5, 8., 8.],
+                &topo.arc_bboxes()
+            ),
+            Ok(geojson_obj)
+        );
+    }
+
+    #[test]
+    fn convert_recomputes_feature_bbox() {
+        // The first geometry has a stale bbox:
+        let topo = decode("{\"arcs\":[[[0,0],[4000,9999],[9999,5000]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"bbox\":[0,0,1,1],\"type\":\"LineString\"},{\"coordinates\":[5000,5000],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"transform\":{\"scale\":[0.001,0.002],\"translate\":[10,20]},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let options = ToGeoJsonOptions {
+            recompute_feature_bbox: true,
+            ..Default::default()
+        };
+        let geojson_obj = to_geojson_with_options(&topo, "example", &options).unwrap();
+        for feature in geojson_obj.features.iter() {
+            let coords = match feature.geometry.as_ref().unwrap().value {
+                geojson::Value::LineString(ref line) => line.clone(),
+                geojson::Value::Point(ref pos) => vec![pos.clone()],
+                _ => unreachable!(),
+            };
+            let fold = |f: fn(f64, f64) -> f64, i: usize| {
+                coords.iter().map(|pos| pos[i]).reduce(f).unwrap()
+            };
+            assert_eq!(
+                feature.bbox,
+                Some(vec![
+                    fold(f64::min, 0),
+                    fold(f64::min, 1),
+                    fold(f64::max, 0),
+                    fold(f64::max, 1),
+                ])
+            );
+        }
+        assert_eq!(geojson_obj.features[1].bbox, Some(vec![15., 30., 15., 30.]));
+    }
+
+    #[test]
+    fn convert_one_geometry_matches_per_type_conversion() {
+        let topo = decode("{\"arcs\":[[[4000,0],[1999,9999],[2000,-9999],[2000,9999]],[[0,0],[0,9999],[2000,0],[0,-9999],[-2000,0]]],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[4000,5000],\"properties\":{\"prop0\":\"value0\"},\"type\":\"Point\"},{\"arcs\":[0],\"properties\":{\"prop0\":\"value0\",\"prop1\":0},\"type\":\"LineString\"},{\"arcs\":[[1]],\"properties\":{\"prop0\":\"value0\",\"prop1\":{\"this\":\"that\"}},\"type\":\"Polygon\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\",\"transform\":{\"scale\":[0.0005000500050005,0.00010001000100010001],\"translate\":[100,0]}}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let geoms = match topo.objects[0].geometry.value {
+            Value::GeometryCollection(ref geoms) => geoms,
+            _ => unreachable!(),
+        };
+        let expected = vec![
+            convert_geom_coords(&geoms[0], &topo.transform).unwrap(),
+            convert_geom_arcs(&geoms[1], &topo.arcs, &topo.transform).unwrap(),
+            convert_geom_arcs(&geoms[2], &topo.arcs, &topo.transform).unwrap(),
+        ];
+        let geojson_obj = to_geojson(&topo, "example").unwrap();
+        assert_eq!(geojson_obj.features, expected);
+        assert_eq!(
+            GeoJson::FeatureCollection(geojson_obj).to_string(),
+            GeoJson::FeatureCollection(geojson::FeatureCollection {
+                features: expected,
+                bbox: None,
+                foreign_members: None,
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn convert_transformed_top_level_points() {
+        // The point coordinates are quantized but not delta-encoded, unlike the arcs:
+        let topo = decode("{\"arcs\":[],\"objects\":{\"city\":{\"coordinates\":[4,10],\"type\":\"Point\"},\"stops\":{\"coordinates\":[[2,2],[4,10]],\"type\":\"MultiPoint\"}},\"transform\":{\"scale\":[0.5,0.1],\"translate\":[100,-5]},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let value = |key: &str| {
+            to_feature(&topo, key)
+                .unwrap()
+                .geometry
+                .map(|geometry| geometry.value)
+        };
+        assert_eq!(value("city"), Some(geojson::Value::Point(vec![102., -4.])));
+        assert_eq!(
+            value("stops"),
+            Some(geojson::Value::MultiPoint(vec![
+                vec![101., -4.8],
+                vec![102., -4.]
+            ]))
+        );
+    }
+
+    #[test]
+    fn convert_omits_empty_geometries() {
+        let topo = decode("{\"arcs\":[[[0,0],[1,1]]],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[],\"id\":\"empty\",\"type\":\"MultiPoint\"},{\"arcs\":[0],\"id\":\"line\",\"type\":\"LineString\"},{\"arcs\":[],\"id\":\"no rings\",\"type\":\"Polygon\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(to_geojson(&topo, "example").unwrap().features.len(), 3);
+
+        let options = ToGeoJsonOptions {
+            omit_empty_geometries: true,
+            ..Default::default()
+        };
+        let features = to_geojson_with_options(&topo, "example", &options)
+            .unwrap()
+            .features;
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0].id, Some(FeatureId::String("line".to_string())));
+    }
+
+    #[test]
+    fn convert_closes_open_rings() {
+        // The ring of the polygon doesn't end where it starts:
+        let topo = decode("{\"arcs\":[[[0,0],[2,0],[2,2],[0,2]]],\"objects\":{\"example\":{\"arcs\":[[0]],\"type\":\"Polygon\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let open_ring = vec![vec![0., 0.], vec![2., 0.], vec![2., 2.], vec![0., 2.]];
+
+        let geojson_obj = to_geojson(&topo, "example").unwrap();
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::Polygon(vec![open_ring.clone()])
+        );
+
+        let options = ToGeoJsonOptions {
+            close_rings: true,
+            ..Default::default()
+        };
+        let geojson_obj = to_geojson_with_options(&topo, "example", &options).unwrap();
+        let mut closed_ring = open_ring;
+        closed_ring.push(vec![0., 0.]);
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::Polygon(vec![closed_ring])
+        );
+    }
+
+    #[test]
+    fn convert_collection_as_single_feature() {
+        let topo = decode("{\"arcs\":[[[0,0],[1,1]]],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[3,3],\"properties\":{\"name\":\"point\"},\"type\":\"Point\"},{\"arcs\":[0],\"type\":\"LineString\"}],\"properties\":{\"name\":\"collection\"},\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let options = ToGeoJsonOptions {
+            collection_as_single_feature: true,
+            use_object_name_as_id: true,
+            ..Default::default()
+        };
+
+        let geojson_obj = to_geojson_with_options(&topo, "example", &options).unwrap();
+        assert_eq!(geojson_obj.features.len(), 1);
+        let feature = &geojson_obj.features[0];
+        assert_eq!(feature.id, Some(FeatureId::String("example".to_string())));
+        assert_eq!(
+            feature.properties.as_ref().unwrap()["name"],
+            json!("collection")
+        );
+        assert_eq!(
+            feature.geometry.as_ref().unwrap().value,
+            geojson::Value::GeometryCollection(vec![
+                geojson::Geometry::new(geojson::Value::Point(vec![3., 3.])),
+                geojson::Geometry::new(geojson::Value::LineString(vec![
+                    vec![0., 0.],
+                    vec![1., 1.]
+                ])),
+            ])
+        );
+    }
+
+    #[test]
+    fn convert_flattens_nested_collections() {
+        let topo = decode("{\"arcs\":[[[0,0],[1,1]]],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[3,3],\"type\":\"Point\"},{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"},{\"coordinates\":[4,4],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let geojson_obj = to_geojson(&topo, "example").unwrap();
+        let values: Vec<_> = geojson_obj
+            .features
+            .into_iter()
+            .map(|f| f.geometry.unwrap().value)
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                geojson::Value::Point(vec![3., 3.]),
+                geojson::Value::LineString(vec![vec![0., 0.], vec![1., 1.]]),
+                geojson::Value::Point(vec![4., 4.]),
+            ]
+        );
+    }
+
+    #[test]
+    fn convert_geometry_values() {
+        let topo = decode("{\"arcs\":[[[0,0],[2,0],[0,2],[-2,0],[0,-2]],[[1,1],[0,1]]],\"objects\":{},\"transform\":{\"scale\":[0.5,0.5],\"translate\":[10,20]},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let line = vec![vec![10.5, 20.5], vec![10.5, 21.]];
+        let ring = vec![
+            vec![10., 20.],
+            vec![11., 20.],
+            vec![11., 21.],
+            vec![10., 21.],
+            vec![10., 20.],
+        ];
+        let mut reversed_line = line.clone();
+        reversed_line.reverse();
+
+        let cases = vec![
+            (
+                Value::Point(vec![1., 2.]),
+                geojson::Value::Point(vec![10.5, 21.]),
+            ),
+            (
+                Value::MultiPoint(vec![vec![0., 0.], vec![2., 2.]]),
+                geojson::Value::MultiPoint(vec![vec![10., 20.], vec![11., 21.]]),
+            ),
+            (
+                Value::LineString(vec![1]),
+                geojson::Value::LineString(line.clone()),
+            ),
+            (
+                Value::MultiLineString(vec![vec![1], vec![-2]]),
+                geojson::Value::MultiLineString(vec![line.clone(), reversed_line]),
+            ),
+            (
+                Value::Polygon(vec![vec![0]]),
+                geojson::Value::Polygon(vec![ring.clone()]),
+            ),
+            (
+                Value::MultiPolygon(vec![vec![vec![0]]]),
+                geojson::Value::MultiPolygon(vec![vec![ring]]),
+            ),
+            (
+                Value::GeometryCollection(vec![crate::Geometry::new(Value::LineString(vec![1]))]),
+                geojson::Value::GeometryCollection(vec![geojson::Geometry::new(
+                    geojson::Value::LineString(line),
+                )]),
+            ),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(
+                geom_value_to_geojson(&value, &topo.arcs, &topo.transform).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn convert_parallel_matches_sequential() {
+        let geometries: Vec<String> = (0..100)
+            .map(|i| {
+                format!(
+                    "{{\"arcs\":[[{}]],\"id\":{},\"type\":\"Polygon\"}}",
+                    i % 2,
+                    i
+                )
+            })
+            .collect();
+        let topo = decode(&format!("{{\"arcs\":[[[0,0],[1,0],[0,1],[-1,0],[0,-1]],[[5,5],[0,1],[1,0],[0,-1],[-1,0]]],\"objects\":{{\"example\":{{\"geometries\":[{}],\"type\":\"GeometryCollection\"}}}},\"transform\":{{\"scale\":[0.5,0.5],\"translate\":[10,20]}},\"type\":\"Topology\"}}", geometries.join(",")))
+            .try_unwrap_topology()
+            .unwrap();
+
+        assert_eq!(
+            crate::to_geojson_parallel(&topo, "example").unwrap(),
+            to_geojson(&topo, "example").unwrap()
+        );
+        assert_eq!(
+            crate::to_geojson_parallel(&topo, "foo"),
+            Err(Error::TopoToGeoUnknownKey("foo".to_string()))
+        );
+    }
+
+    #[test]
+    fn convert_keeps_id_types() {
+        let topo = decode("{\"arcs\":[],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[0,0],\"id\":42,\"type\":\"Point\"},{\"coordinates\":[0,0],\"id\":\"42\",\"type\":\"Point\"},{\"coordinates\":[0,0],\"id\":null,\"type\":\"Point\"},{\"coordinates\":[0,0],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let geojson_obj = to_geojson(&topo, "example").unwrap();
+        let ids: Vec<_> = geojson_obj.features.iter().map(|f| f.id.clone()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                Some(geojson::feature::Id::Number(42.into())),
+                Some(geojson::feature::Id::String("42".to_string())),
+                None,
+                None,
+            ]
+        );
+        let written = GeoJson::FeatureCollection(geojson_obj).to_string();
+        let written: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(written["features"][0]["id"], serde_json::json!(42));
+        assert_eq!(written["features"][1]["id"], serde_json::json!("42"));
+    }
+
+    #[test]
+    fn convert_splits_multi_outer_polygons() {
+        // A polygon with two disjoint exterior rings, the second one having a hole:
+        let topo = decode("{\"arcs\":[[[0,0],[1,0],[1,1],[0,1],[0,0]],[[5,5],[9,5],[9,9],[5,9],[5,5]],[[6,6],[6,7],[7,7],[7,6],[6,6]]],\"objects\":{\"example\":{\"arcs\":[[0],[1],[2]],\"type\":\"Polygon\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let rings: Vec<Vec<Vec<f64>>> = topo.arcs.clone();
+
+        let options = ToGeoJsonOptions {
+            split_multi_outer_polygons: true,
+            ..Default::default()
+        };
+        let geojson_obj = to_geojson_with_options(&topo, "example", &options).unwrap();
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::MultiPolygon(vec![
+                vec![rings[0].clone()],
+                vec![rings[1].clone(), rings[2].clone()],
+            ])
+        );
+
+        // Polygons with a single exterior ring are kept as is:
+        let topo = decode("{\"arcs\":[[[5,5],[9,5],[9,9],[5,9],[5,5]],[[6,6],[6,7],[7,7],[7,6],[6,6]]],\"objects\":{\"example\":{\"arcs\":[[0],[1]],\"type\":\"Polygon\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(
+            to_geojson_with_options(&topo, "example", &options),
+            to_geojson(&topo, "example")
+        );
+    }
+
+    #[test]
+    fn convert_reassigns_holes_by_containment() {
+        // The hole (arc 2) of the second square is listed with the first one:
+        let topo = decode("{\"arcs\":[[[0,0],[4,0],[4,4],[0,4],[0,0]],[[5,5],[9,5],[9,9],[5,9],[5,5]],[[6,6],[6,7],[7,7],[7,6],[6,6]]],\"objects\":{\"example\":{\"arcs\":[[[0],[2]],[[1]]],\"type\":\"MultiPolygon\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let rings: Vec<Vec<Vec<f64>>> = topo.arcs.clone();
+
+        let options = ToGeoJsonOptions {
+            reassign_holes_by_containment: true,
+            ..Default::default()
+        };
+        let geojson_obj = to_geojson_with_options(&topo, "example", &options).unwrap();
+        assert_eq!(
+            geojson_obj.features[0].geometry.as_ref().unwrap().value,
+            geojson::Value::MultiPolygon(vec![
+                vec![rings[0].clone()],
+                vec![rings[1].clone(), rings[2].clone()],
+            ])
+        );
+    }
+
+    #[test]
+    fn convert_swaps_xy() {
+        // Positions in [lat, lon] order, with an arc shared by both lines:
+        let topo = decode("{\"arcs\":[[[48.8,2.3],[45.7,4.8]],[[45.7,4.8],[43.3,5.4]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0,1],\"bbox\":[43.3,2.3,48.8,5.4],\"type\":\"LineString\"},{\"arcs\":[-2],\"type\":\"LineString\"},{\"coordinates\":[48.8,2.3],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let options = ToGeoJsonOptions {
+            swap_xy: true,
+            ..Default::default()
+        };
+        let geojson_obj = to_geojson_with_options(&topo, "example", &options).unwrap();
+        let values: Vec<_> = geojson_obj
+            .features
+            .iter()
+            .map(|f| f.geometry.as_ref().unwrap().value.clone())
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                geojson::Value::LineString(vec![vec![2.3, 48.8], vec![4.8, 45.7], vec![5.4, 43.3]]),
+                geojson::Value::LineString(vec![vec![5.4, 43.3], vec![4.8, 45.7]]),
+                geojson::Value::Point(vec![2.3, 48.8]),
+            ]
+        );
+        assert_eq!(
+            geojson_obj.features[0].bbox,
+            Some(vec![2.3, 43.3, 5.4, 48.8])
+        );
+    }
+
+    #[test]
+    fn convert_uses_object_name_as_id() {
+        let topo = decode("{\"arcs\":[[[0.0,0.0],[1.0,1.0]]],\"objects\":{\"road\":{\"arcs\":[0],\"type\":\"LineString\"},\"named\":{\"arcs\":[0],\"id\":7,\"type\":\"LineString\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let options = ToGeoJsonOptions {
+            use_object_name_as_id: true,
+            ..Default::default()
+        };
+
+        let road = to_geojson_with_options(&topo, "road", &options).unwrap();
+        assert_eq!(road.features[0].id, Some(FeatureId::String("road".into())));
+        // An existing id is kept:
+        let named = to_geojson_with_options(&topo, "named", &options).unwrap();
+        assert_eq!(named.features[0].id, Some(FeatureId::Number(7.into())));
+        assert_eq!(to_geojson(&topo, "road").unwrap().features[0].id, None);
+    }
+
+    #[test]
+    fn convert_with_reproject() {
+        let topo = decode("{\"arcs\":[[[0.0,0.0],[1.0,1.0]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"},{\"coordinates\":[2.0,3.0],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let geojson_obj = to_geojson_with_reproject(&topo, "example", |pos| {
+            pos.iter_mut().for_each(|v| *v += 1.)
+        })
+        .unwrap();
+        let values: Vec<_> = geojson_obj
+            .features
+            .iter()
+            .map(|f| f.geometry.as_ref().unwrap().value.clone())
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                geojson::Value::LineString(vec![vec![1., 1.], vec![2., 2.]]),
+                geojson::Value::Point(vec![3., 4.]),
+            ]
+        );
+    }
+
+    #[test]
+    fn convert_fails_arc_index_out_of_bounds() {
+        let topo_json_str = format!("{{\"arcs\":[[[0.0,0.0],[1.0,1.0]]],\"objects\":{{\"example\":{{\"arcs\":[0,{}],\"type\":\"LineString\"}}}},\"type\":\"Topology\"}}", i32::MIN);
+        let topo = decode(&topo_json_str).try_unwrap_topology().unwrap();
+        assert_eq!(
+            to_geojson(&topo, "example").err(),
+            Some(Error::ArcIndexOutOfBounds(i32::MIN as i64))
+        );
+
+        // Indexes which don't fit in an i32 are rejected when parsing:
+        let topo_json_str = topo_json_str.replace(&i32::MIN.to_string(), "4294967296");
+        assert_eq!(
+            topo_json_str.parse::<TopoJson>().err(),
+            Some(Error::At {
+                path: String::from("object 'example'"),
+                source: Box::new(Error::ArcIndexOutOfBounds(4294967296)),
+            })
+        );
+    }
+
+    #[test]
+    fn convert_merges_keys() {
+        let topo = decode("{\"arcs\":[[[0.0,0.0],[1.0,1.0]]],\"objects\":{\"lines\":{\"arcs\":[0],\"type\":\"LineString\"},\"points\":{\"geometries\":[{\"coordinates\":[0.0,0.0],\"type\":\"Point\"},{\"coordinates\":[1.0,1.0],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let merged = to_geojson_keys(&topo, &["points", "lines"]).unwrap();
+        let mut expected = to_geojson(&topo, "points").unwrap().features;
+        expected.append(&mut to_geojson(&topo, "lines").unwrap().features);
+        assert_eq!(merged.features, expected);
+
+        assert_eq!(
+            to_geojson_keys(&topo, &["lines", "roads"]).err(),
+            Some(Error::TopoToGeoUnknownKey(String::from("roads")))
+        );
+    }
+
+    #[test]
+    fn convert_lenient() {
+        let topo = decode("{\"arcs\":[[[0.0,0.0],[1.0,1.0]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"id\":\"a\",\"type\":\"LineString\"},{\"arcs\":[4],\"id\":\"bad\",\"type\":\"LineString\"},{\"coordinates\":[0.0,0.0],\"id\":\"c\",\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        assert!(to_geojson(&topo, "example").is_err());
+
+        let (collection, errors) = to_geojson_lenient(&topo, "example");
+        let ids: Vec<_> = collection.features.iter().map(|f| f.id.clone()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                Some(FeatureId::String(String::from("a"))),
+                Some(FeatureId::String(String::from("c")))
+            ]
+        );
+        assert_eq!(errors, vec![(1, Error::ArcIndexOutOfBounds(4))]);
+
+        let (collection, errors) = to_geojson_lenient(&topo, "roads");
+        assert!(collection.features.is_empty());
+        assert_eq!(
+            errors,
+            vec![(0, Error::TopoToGeoUnknownKey(String::from("roads")))]
+        );
+    }
+
+    #[test]
+    fn convert_by_type() {
+        let topo = decode("{\"arcs\":[[[0.0,0.0],[1.0,1.0]],[[1.0,1.0],[0.0,1.0],[0.0,0.0]]],\"objects\":{\"lines\":{\"arcs\":[0],\"id\":\"line\",\"type\":\"LineString\"},\"mixed\":{\"geometries\":[{\"coordinates\":[0.0,0.0],\"type\":\"Point\"},{\"arcs\":[[0,1]],\"type\":\"Polygon\"},{\"geometries\":[{\"arcs\":[1],\"id\":\"nested\",\"type\":\"LineString\"}],\"type\":\"GeometryCollection\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+
+        let lines = to_geojson_by_type(&topo, &["LineString", "MultiLineString"]).unwrap();
+        let ids: Vec<_> = lines.features.iter().map(|f| f.id.clone()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                Some(FeatureId::String(String::from("line"))),
+                Some(FeatureId::String(String::from("nested")))
+            ]
+        );
+        assert!(lines.features.iter().all(|f| matches!(
+            f.geometry.as_ref().unwrap().value,
+            geojson::Value::LineString(..)
+        )));
+
+        assert!(to_geojson_by_type(&topo, &["GeometryCollection"])
+            .unwrap()
+            .features
+            .is_empty());
+    }
+
+    #[test]
+    fn convert_sorts_features_spatially() {
+        let topo = decode("{\"arcs\":[[[9.0,9.0],[10.0,10.0]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"id\":\"ne\",\"type\":\"LineString\"},{\"coordinates\":[0.0,10.0],\"id\":\"nw\",\"type\":\"Point\"},{\"coordinates\":[[10.0,0.0],[9.0,1.0]],\"id\":\"se\",\"type\":\"MultiPoint\"},{\"coordinates\":[0.5,0.5],\"id\":\"sw\",\"type\":\"Point\"},{\"coordinates\":[0.5,0.5],\"id\":\"sw2\",\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let options = ToGeoJsonOptions {
+            spatial_sort: true,
+            ..Default::default()
+        };
+        let ids: Vec<_> = to_geojson_with_options(&topo, "example", &options)
+            .unwrap()
+            .features
+            .into_iter()
+            .map(|f| f.id.unwrap())
+            .collect();
+        // Z-order, features with the same centroid keeping their order:
+        let expected: Vec<_> = ["sw", "sw2", "se", "nw", "ne"]
+            .iter()
+            .map(|id| FeatureId::String(id.to_string()))
+            .collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn write_geojson_files_per_object() {
+        let topo = decode("{\"arcs\":[[[0,0],[1,1]]],\"objects\":{\"a/b\":{\"coordinates\":[0,0],\"type\":\"Point\"},\"line\":{\"arcs\":[0],\"type\":\"LineString\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("topojson-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths = crate::write_geojson_files(&topo, &dir).unwrap();
+        assert_eq!(
+            paths,
+            vec![dir.join("a_b.geojson"), dir.join("line.geojson")]
+        );
+        for ((name, expected), path) in crate::to_geojson_all(&topo).unwrap().into_iter().zip(paths)
+        {
+            let written = std::fs::read_to_string(path)
+                .unwrap()
+                .parse::<GeoJson>()
+                .unwrap();
+            assert_eq!(written, GeoJson::FeatureCollection(expected), "{}", name);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            crate::write_geojson_files(&topo, &dir),
+            Err(Error::Io { .. })
+        ));
+
+        // "a/b" and "a_b" would both be written to "a_b.geojson":
+        let topo = decode("{\"arcs\":[],\"objects\":{\"a/b\":{\"coordinates\":[0,0],\"type\":\"Point\"},\"a_b\":{\"coordinates\":[1,1],\"type\":\"Point\"}},\"type\":\"Topology\"}")
+            .try_unwrap_topology()
+            .unwrap();
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(
+            crate::write_geojson_files(&topo, &dir),
+            Err(Error::DuplicateFileName(String::from("a_b.geojson")))
+        );
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,195 @@
+// Copyright 2018 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{to_geojson, Error, Position, Topology};
+use geojson::Value as GeoJsonGeomValue;
+
+fn write_position(out: &mut String, pos: &[f64]) {
+    let coords: Vec<String> = pos.iter().map(|c| c.to_string()).collect();
+    out.push_str(&coords.join(" "));
+}
+
+fn write_line(out: &mut String, positions: &[Position]) {
+    if positions.is_empty() {
+        out.push_str("EMPTY");
+        return;
+    }
+    out.push('(');
+    for (i, pos) in positions.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_position(out, pos);
+    }
+    out.push(')');
+}
+
+// A position without coordinates is the empty point (`POINT ()` isn't valid WKT):
+fn write_point(out: &mut String, pos: &[f64]) {
+    if pos.is_empty() {
+        out.push_str("EMPTY");
+        return;
+    }
+    out.push('(');
+    write_position(out, pos);
+    out.push(')');
+}
+
+fn write_ring(out: &mut String, ring: &[Position]) {
+    // WKT rings must repeat their first position at the end:
+    if !ring.is_empty() && ring.first() != ring.last() {
+        let mut closed = ring.to_vec();
+        closed.push(ring[0].clone());
+        write_line(out, &closed);
+    } else {
+        write_line(out, ring);
+    }
+}
+
+fn write_polygon(out: &mut String, rings: &[Vec<Position>]) {
+    if rings.is_empty() {
+        out.push_str("EMPTY");
+        return;
+    }
+    out.push('(');
+    for (i, ring) in rings.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_ring(out, ring);
+    }
+    out.push(')');
+}
+
+fn write_list<T, F: Fn(&mut String, &T)>(out: &mut String, items: &[T], write_item: F) {
+    if items.is_empty() {
+        out.push_str("EMPTY");
+        return;
+    }
+    out.push('(');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_item(out, item);
+    }
+    out.push(')');
+}
+
+fn write_geometry(out: &mut String, value: &GeoJsonGeomValue) {
+    match value {
+        GeoJsonGeomValue::Point(pos) => {
+            out.push_str("POINT ");
+            write_point(out, pos);
+        }
+        GeoJsonGeomValue::MultiPoint(positions) => {
+            out.push_str("MULTIPOINT ");
+            write_list(out, positions, |out, pos| write_point(out, pos));
+        }
+        GeoJsonGeomValue::LineString(positions) => {
+            out.push_str("LINESTRING ");
+            write_line(out, positions);
+        }
+        GeoJsonGeomValue::MultiLineString(lines) => {
+            out.push_str("MULTILINESTRING ");
+            write_list(out, lines, |out, line| write_line(out, line));
+        }
+        GeoJsonGeomValue::Polygon(rings) => {
+            out.push_str("POLYGON ");
+            write_polygon(out, rings);
+        }
+        GeoJsonGeomValue::MultiPolygon(polygons) => {
+            out.push_str("MULTIPOLYGON ");
+            write_list(out, polygons, |out, rings| write_polygon(out, rings));
+        }
+        GeoJsonGeomValue::GeometryCollection(geometries) => {
+            out.push_str("GEOMETRYCOLLECTION ");
+            write_list(out, geometries, |out, g| write_geometry(out, &g.value));
+        }
+    }
+}
+
+/// Convert a TopoJSON Topology object to WKT strings, one per feature.
+///
+/// The arcs are stitched and decoded in the same way than [`to_geojson`]
+/// (features without geometry produce `GEOMETRYCOLLECTION EMPTY`).
+pub fn to_wkt(topo: &Topology, key: &str) -> Result<Vec<String>, Error> {
+    let collection = to_geojson(topo, key)?;
+    Ok(collection
+        .features
+        .iter()
+        .map(|feature| {
+            let mut out = String::new();
+            match feature.geometry {
+                Some(ref geometry) => write_geometry(&mut out, &geometry.value),
+                None => out.push_str("GEOMETRYCOLLECTION EMPTY"),
+            }
+            out
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{to_wkt, TopoJson};
+
+    #[test]
+    fn convert_quantized_topology_example_specifications_to_wkt() {
+        // This is the quantized example from https://github.com/topojson/topojson-specification#11-examples
+        let topo_json_str = "{\"arcs\":[[[4000,0],[1999,9999],[2000,-9999],[2000,9999]],[[0,0],[0,9999],[2000,0],[0,-9999],[-2000,0]]],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[4000,5000],\"properties\":{\"prop0\":\"value0\"},\"type\":\"Point\"},{\"arcs\":[0],\"properties\":{\"prop0\":\"value0\",\"prop1\":0},\"type\":\"LineString\"},{\"arcs\":[[1]],\"properties\":{\"prop0\":\"value0\",\"prop1\":{\"this\":\"that\"}},\"type\":\"Polygon\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\",\"transform\":{\"scale\":[0.0005,0.0001],\"translate\":[100,0]}}";
+        let topo = topo_json_str
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+
+        let wkt = to_wkt(&topo, "example").expect("Unable to convert to WKT");
+        assert_eq!(wkt.len(), 3);
+        assert_eq!(wkt[0], "POINT (102 0.5)");
+        assert!(wkt[1].starts_with("LINESTRING (102 0, "));
+        assert_eq!(
+            wkt[2],
+            "POLYGON ((100 0, 100 0.9999, 101 0.9999, 101 0, 100 0))"
+        );
+    }
+
+    #[test]
+    fn convert_multipolygon_to_wkt_closes_rings() {
+        let topo_json_str = "{\"arcs\":[[[0,0],[1,0],[1,1],[0,1]],[[2,2],[3,2],[3,3],[2,2]]],\"objects\":{\"example\":{\"arcs\":[[[0]],[[1]]],\"type\":\"MultiPolygon\"}},\"type\":\"Topology\"}";
+        let topo = topo_json_str
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+
+        let wkt = to_wkt(&topo, "example").unwrap();
+        assert_eq!(
+            wkt,
+            vec!["MULTIPOLYGON (((0 0, 1 0, 1 1, 0 1, 0 0)), ((2 2, 3 2, 3 3, 2 2)))"]
+        );
+    }
+
+    #[test]
+    fn convert_empty_points_to_wkt() {
+        let topo_json_str = "{\"arcs\":[],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[],\"type\":\"Point\"},{\"coordinates\":[[1,2],[]],\"type\":\"MultiPoint\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}";
+        let topo = topo_json_str
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+
+        let wkt = to_wkt(&topo, "example").unwrap();
+        assert_eq!(wkt, vec!["POINT EMPTY", "MULTIPOINT ((1 2), EMPTY)"]);
+    }
+}
@@ -13,10 +13,12 @@
 // limitations under the License.
 
 use std::fmt;
+use std::io::Read;
 use std::str::FromStr;
 
 use crate::json::{self, Deserialize, Deserializer, JsonObject, Serialize, Serializer};
-use crate::{Error, Geometry, Topology};
+use crate::util::OrderedObject;
+use crate::{member_names, util, Error, Geometry, Topology};
 
 /// TopoJSON Objects (either Topology or Geometry)
 ///
@@ -50,11 +52,56 @@ impl From<Topology> for TopoJson {
     }
 }
 
+/// Options for parsing TopoJSON with [`TopoJson::from_str_with_options`]
+/// or the `from_json_object_with_options` functions.
+///
+/// All the options are disabled by default, which gives the same (strict)
+/// parsing than [`TopoJson::from_str`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Also accept the non-standard `{"x": .., "y": ..}` object form for the
+    /// 'scale' and 'translate' members of the transform.
+    pub lenient_transform: bool,
+    /// Keep geometries with an unrecognized type as [`Value::Unknown`](crate::Value::Unknown)
+    /// (preserving their raw object) instead of failing to parse.
+    pub unknown_geometry_passthrough: bool,
+    /// The maximum number of items of the arrays of positions, coordinates, arc
+    /// indexes and geometries, above which parsing fails with [`Error::InputTooLarge`].
+    /// `None` uses [`DEFAULT_MAX_ARRAY_LEN`].
+    ///
+    /// The limit is checked once the input has been read into a `serde_json::Value`,
+    /// so it only bounds the storage allocated for the crate's own types (positions,
+    /// arcs, geometries): it doesn't protect against the memory used by serde_json
+    /// itself, which grows with the size of the input. Limit the size of untrusted
+    /// inputs before parsing them.
+    pub max_array_len: Option<usize>,
+    /// Also accept numbers encoded as JSON strings (like `"100.0"`, as written by
+    /// some exporters) for the positions, the arc indexes and the transform.
+    /// Non-finite values (`"NaN"`, `"inf"`) are still rejected.
+    pub numeric_strings: bool,
+}
+
+/// The default value of [`ParseOptions::max_array_len`].
+pub const DEFAULT_MAX_ARRAY_LEN: usize = 1 << 28;
+
+impl ParseOptions {
+    pub(crate) fn array_len_limit(&self) -> usize {
+        self.max_array_len.unwrap_or(DEFAULT_MAX_ARRAY_LEN)
+    }
+}
+
 impl TopoJson {
     pub fn from_json_object(object: JsonObject) -> Result<Self, Error> {
-        let type_ = match object.get("type") {
+        TopoJson::from_json_object_with_options(object, &ParseOptions::default())
+    }
+
+    pub fn from_json_object_with_options(
+        object: JsonObject,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        let type_ = match object.get(member_names::TYPE) {
             Some(json::JsonValue::String(t)) => Type::from_str(t),
-            _ => return Err(Error::ExpectedProperty("type".to_owned())),
+            _ => return Err(Error::ExpectedProperty(member_names::TYPE.to_owned())),
         };
         let type_ = type_.ok_or(Error::TopoJsonUnknownType)?;
         match type_ {
@@ -65,12 +112,76 @@ impl TopoJson {
             | Type::Polygon
             | Type::MultiPolygon
             | Type::GeometryCollection => {
-                Geometry::from_json_object(object).map(TopoJson::Geometry)
+                Geometry::from_json_object_with_options(object, options).map(TopoJson::Geometry)
+            }
+            Type::Topology => {
+                Topology::from_json_object_with_options(object, options).map(TopoJson::Topology)
             }
-            Type::Topology => Topology::from_json_object(object).map(TopoJson::Topology),
         }
     }
 
+    /// Parse a TopoJSON string according to `options`.
+    pub fn from_str_with_options(s: &str, options: &ParseOptions) -> Result<Self, Error> {
+        get_object(s)?.into_topojson(options)
+    }
+
+    /// Parse the TopoJSON object found at the JSON `pointer` (such as `/data`)
+    /// within a larger JSON document `value`.
+    ///
+    /// Fails with [`Error::JsonPointerNotFound`] if there is no value at `pointer`,
+    /// and with [`Error::ExpectedObjectValue`] if it isn't an object.
+    ///
+    /// Unlike when parsing JSON text, the objects of a topology follow the key order
+    /// of the 'objects' member of `value`: a `serde_json::Value` only keeps the input
+    /// order when the `preserve_order` feature of serde_json is enabled.
+    pub fn from_json_at_pointer(value: &json::JsonValue, pointer: &str) -> Result<Self, Error> {
+        let target = value
+            .pointer(pointer)
+            .ok_or_else(|| Error::JsonPointerNotFound(pointer.to_owned()))?;
+        if !target.is_object() {
+            return Err(Error::ExpectedObjectValue);
+        }
+        OrderedObject::deserialize(target)
+            .map_err(util::from_json_error)?
+            .into_topojson(&ParseOptions::default())
+    }
+
+    /// Read a TopoJSON object from `reader`.
+    ///
+    /// Its content must be valid UTF-8, otherwise [`Error::InvalidUtf8`] is returned
+    /// (rather than [`Error::MalformedJson`], which is kept for invalid JSON text).
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        TopoJson::from_slice(&bytes)
+    }
+
+    /// Parse a TopoJSON object from `bytes`, without copying them: for example
+    /// the content of a memory-mapped file, which must then not be modified
+    /// while it is parsed.
+    ///
+    /// The errors are the same as with [`TopoJson::from_reader`].
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        let s = std::str::from_utf8(bytes).map_err(|e| Error::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        })?;
+        s.parse()
+    }
+
+    /// Read a stream of TopoJSON objects from `reader`, such as newline-delimited
+    /// TopoJSON (one object per line). Whitespace, including blank lines, is skipped.
+    ///
+    /// The iterator stops after the first malformed JSON text.
+    pub fn from_ndjson_reader<R: Read>(reader: R) -> impl Iterator<Item = Result<Self, Error>> {
+        serde_json::Deserializer::from_reader(reader)
+            .into_iter::<OrderedObject>()
+            .map(|object| {
+                object
+                    .map_err(util::from_json_error)
+                    .and_then(|o| o.into_topojson(&ParseOptions::default()))
+            })
+    }
+
     pub fn try_unwrap_geometry(self) -> Option<Geometry> {
         match self {
             TopoJson::Geometry(i) => Some(i),
@@ -130,9 +241,10 @@ impl<'de> Deserialize<'de> for TopoJson {
     {
         use serde::de::Error as SerdeError;
 
-        let val = JsonObject::deserialize(deserializer)?;
+        let val = OrderedObject::deserialize(deserializer)?;
 
-        TopoJson::from_json_object(val).map_err(D::Error::custom)
+        val.into_topojson(&ParseOptions::default())
+            .map_err(D::Error::custom)
     }
 }
 
@@ -140,25 +252,12 @@ impl FromStr for TopoJson {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let object = get_object(s)?;
-
-        TopoJson::from_json_object(object)
+        TopoJson::from_str_with_options(s, &ParseOptions::default())
     }
 }
 
-fn get_object(s: &str) -> Result<json::JsonObject, Error> {
-    ::serde_json::from_str(s)
-        .ok()
-        .and_then(json_value_into_json_object)
-        .ok_or(Error::MalformedJson)
-}
-
-fn json_value_into_json_object(json_value: json::JsonValue) -> Option<json::JsonObject> {
-    if let json::JsonValue::Object(geo) = json_value {
-        Some(geo)
-    } else {
-        None
-    }
+fn get_object(s: &str) -> Result<OrderedObject, Error> {
+    ::serde_json::from_str(s).map_err(|_| Error::MalformedJson)
 }
 
 impl fmt::Display for TopoJson {
@@ -168,3 +267,128 @@ impl fmt::Display for TopoJson {
             .and_then(|s| f.write_str(&s))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{to_geojson, Error, TopoJson, Topology};
+
+    #[test]
+    fn from_reader() {
+        let topo_json_str = "{\"arcs\":[],\"objects\":{},\"type\":\"Topology\"}";
+        assert_eq!(
+            TopoJson::from_reader(topo_json_str.as_bytes()).unwrap(),
+            topo_json_str.parse::<TopoJson>().unwrap()
+        );
+
+        // Not text at all, unlike a text which isn't JSON:
+        let bytes: &[u8] = b"{\"type\":\"Topology\xff\"}";
+        assert_eq!(
+            TopoJson::from_reader(bytes).err(),
+            Some(Error::InvalidUtf8 { valid_up_to: 17 })
+        );
+        assert_eq!(
+            TopoJson::from_reader("{\"type\":".as_bytes()).err(),
+            Some(Error::MalformedJson)
+        );
+    }
+
+    #[test]
+    fn from_json_at_pointer() {
+        let document = json!({
+            "data": {"arcs": [], "objects": {}, "type": "Topology"},
+            "meta": {"version": 2},
+        });
+        assert_eq!(
+            TopoJson::from_json_at_pointer(&document, "/data"),
+            Ok(TopoJson::Topology(Topology::default()))
+        );
+        assert_eq!(
+            TopoJson::from_json_at_pointer(&document, "/meta/version").err(),
+            Some(Error::ExpectedObjectValue)
+        );
+        assert_eq!(
+            TopoJson::from_json_at_pointer(&document, "/topology").err(),
+            Some(Error::JsonPointerNotFound("/topology".to_string()))
+        );
+
+        // The objects follow the keys of the 'objects' member of the value:
+        let document = json!({
+            "data": {
+                "arcs": [],
+                "objects": {
+                    "zeta": {"coordinates": [0, 0], "type": "Point"},
+                    "alpha": {"coordinates": [1, 1], "type": "Point"},
+                },
+                "type": "Topology",
+            },
+        });
+        let topo = TopoJson::from_json_at_pointer(&document, "/data")
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        let keys: Vec<_> = document["data"]["objects"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(topo.list_names(), keys);
+    }
+
+    #[test]
+    fn objects_keep_input_order() {
+        let topo_json_str = "{\"arcs\":[[[0,0],[1,1]]],\"objects\":{\"zeta\":{\"geometries\":[{\"coordinates\":[3,3],\"id\":\"c\",\"type\":\"Point\"},{\"arcs\":[0],\"id\":\"a\",\"type\":\"LineString\"},{\"coordinates\":[1,1],\"id\":\"b\",\"type\":\"Point\"}],\"type\":\"GeometryCollection\"},\"alpha\":{\"coordinates\":[0,0],\"type\":\"Point\"},\"mu\":{\"coordinates\":[0,0],\"type\":\"Point\"}},\"type\":\"Topology\"}";
+        let names = |topo: &Topology| topo.list_names();
+        let expected = vec!["zeta", "alpha", "mu"];
+
+        let topo = topo_json_str
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(names(&topo), expected);
+        let deserialized: Topology = serde_json::from_str(topo_json_str).unwrap();
+        assert_eq!(names(&deserialized), expected);
+        let lazy = Topology::from_reader_lazy(topo_json_str.as_bytes()).unwrap();
+        assert_eq!(lazy.objects, topo.objects);
+
+        // The features follow the order of the geometries:
+        let ids: Vec<_> = to_geojson(&topo, "zeta")
+            .unwrap()
+            .features
+            .into_iter()
+            .map(|f| f.id.unwrap())
+            .collect();
+        assert_eq!(
+            ids,
+            ["c", "a", "b"].map(|id| geojson::feature::Id::String(id.to_string()))
+        );
+    }
+
+    #[test]
+    fn from_slice() {
+        let bytes = b"{\"coordinates\":[1.0,2.0],\"type\":\"Point\"}";
+        assert!(matches!(
+            TopoJson::from_slice(bytes),
+            Ok(TopoJson::Geometry(..))
+        ));
+        assert_eq!(
+            TopoJson::from_slice(b"\xff{}").err(),
+            Some(Error::InvalidUtf8 { valid_up_to: 0 })
+        );
+    }
+
+    #[test]
+    fn from_ndjson_reader() {
+        let lines = "{\"arcs\":[],\"objects\":{},\"type\":\"Topology\"}\n\n{\"coordinates\":[1.0,2.0],\"type\":\"Point\"}\n";
+        let parsed: Vec<_> = TopoJson::from_ndjson_reader(lines.as_bytes()).collect();
+        assert_eq!(parsed.len(), 2);
+        assert!(matches!(parsed[0], Ok(TopoJson::Topology(..))));
+        assert!(matches!(parsed[1], Ok(TopoJson::Geometry(..))));
+
+        let mut parsed = TopoJson::from_ndjson_reader("{\"type\":\"Curve\"}\n{".as_bytes());
+        assert_eq!(parsed.next(), Some(Err(Error::TopoJsonUnknownType)));
+        assert_eq!(parsed.next(), Some(Err(Error::MalformedJson)));
+        assert_eq!(parsed.next(), None);
+    }
+}
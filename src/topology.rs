@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::json::{Deserialize, Deserializer, JsonObject, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
 
-use crate::{util, Arc, Bbox, Error, NamedGeometry, TopoJson};
+use crate::json::{Deserialize, Deserializer, JsonObject, JsonValue, Serialize, Serializer};
+
+use crate::{
+    algorithm, member_names, to_geojson, util, Arc, ArcIndexes, Bbox, Error, Geometry,
+    NamedGeometry, ParseOptions, Position, TopoJson, Value,
+};
 
 /// Transforms
 ///
 /// [TopoJSON Format Specification § 2.1.2](https://github.com/topojson/topojson-specification#212-transforms)
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct TransformParams {
     pub scale: [f64; 2],
     pub translate: [f64; 2],
@@ -29,11 +35,11 @@ impl<'a> From<&'a TransformParams> for JsonObject {
     fn from(transform: &'a TransformParams) -> JsonObject {
         let mut map = JsonObject::new();
         map.insert(
-            String::from("scale"),
+            String::from(member_names::SCALE),
             ::serde_json::to_value(transform.scale).unwrap(),
         );
         map.insert(
-            String::from("translate"),
+            String::from(member_names::TRANSLATE),
             ::serde_json::to_value(transform.translate).unwrap(),
         );
         map
@@ -42,9 +48,17 @@ impl<'a> From<&'a TransformParams> for JsonObject {
 
 impl TransformParams {
     pub fn from_json_object(mut object: JsonObject) -> Result<Self, Error> {
-        let scale_translate = util::get_scale_translate(&mut object)?;
+        let scale_translate = util::get_scale_translate(&mut object, &ParseOptions::default())?;
         Ok(scale_translate.unwrap())
     }
+
+    /// Whether the transform maps quantized positions to themselves (its scale is
+    /// `[1, 1]` and its translate is `[0, 0]`).
+    ///
+    /// The arcs of a topology with such a transform are still delta-encoded.
+    pub fn is_identity(&self) -> bool {
+        self.scale == [1., 1.] && self.translate == [0., 0.]
+    }
 }
 
 impl Serialize for TransformParams {
@@ -69,10 +83,38 @@ impl<'de> Deserialize<'de> for TransformParams {
     }
 }
 
+/// The order in which the members of a Topology are serialized
+/// by [`Topology::to_string_with_member_order`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemberOrder {
+    /// The order used by the `Serialize` implementation of this crate.
+    Canonical,
+    /// The order used by mapshaper and topojson-server:
+    /// `type`, `transform`, `bbox`, `objects`, `arcs`, then the foreign members.
+    Mapshaper,
+}
+
+/// The area and perimeter of a polygon feature, as computed by
+/// [`Topology::polygon_metrics`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PolygonMetrics {
+    /// The area of the exterior rings, minus the area of the holes.
+    pub area: f64,
+    /// The total length of the rings (exterior rings and holes).
+    pub perimeter: f64,
+}
+
 /// Topology object
 ///
 /// [TopoJSON Format Specification § 2.1](https://github.com/topojson/topojson-specification#21-topology-objects)
-#[derive(Clone, Debug, PartialEq)]
+///
+/// The default `Topology` has no arcs, no objects and no bbox, transform or foreign members.
+///
+/// When parsing JSON text, the objects are kept in the order of the 'objects' member,
+/// whether or not the `preserve_order` feature of serde_json is enabled. When parsing
+/// a `JsonObject` (with `from_json_object`) or a `serde_json::Value` (with
+/// `TopoJson::from_json_at_pointer`), they follow the order of its keys.
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Topology {
     pub bbox: Option<Bbox>,
     pub objects: Vec<NamedGeometry>,
@@ -84,9 +126,9 @@ pub struct Topology {
 impl<'a> From<&'a Topology> for JsonObject {
     fn from(topo: &'a Topology) -> JsonObject {
         let mut map = JsonObject::new();
-        map.insert(String::from("type"), json!("Topology"));
+        map.insert(String::from(member_names::TYPE), json!("Topology"));
         map.insert(
-            String::from("arcs"),
+            String::from(member_names::ARCS),
             serde_json::to_value(&topo.arcs).unwrap(),
         );
 
@@ -97,21 +139,30 @@ impl<'a> From<&'a Topology> for JsonObject {
                 serde_json::to_value(named_geom.geometry.clone()).unwrap(),
             );
         }
-        map.insert(String::from("objects"), serde_json::Value::Object(objects));
+        map.insert(
+            String::from(member_names::OBJECTS),
+            serde_json::Value::Object(objects),
+        );
 
         if let Some(ref bbox) = topo.bbox {
-            map.insert(String::from("bbox"), serde_json::to_value(bbox).unwrap());
+            map.insert(
+                String::from(member_names::BBOX),
+                serde_json::to_value(bbox).unwrap(),
+            );
         }
 
         if let Some(ref transform_params) = topo.transform {
             map.insert(
-                String::from("transform"),
+                String::from(member_names::TRANSFORM),
                 serde_json::to_value(transform_params).unwrap(),
             );
         }
+        // Foreign members never replace (or stand for) the members of the specification:
         if let Some(ref foreign_members) = topo.foreign_members {
             for (key, value) in foreign_members {
-                map.insert(key.to_owned(), value.to_owned());
+                if !member_names::TOPOLOGY_MEMBERS.contains(&key.as_str()) {
+                    map.insert(key.to_owned(), value.to_owned());
+                }
             }
         }
 
@@ -120,13 +171,20 @@ impl<'a> From<&'a Topology> for JsonObject {
 }
 
 impl Topology {
-    pub fn from_json_object(mut object: JsonObject) -> Result<Self, Error> {
+    pub fn from_json_object(object: JsonObject) -> Result<Self, Error> {
+        Topology::from_json_object_with_options(object, &ParseOptions::default())
+    }
+
+    pub fn from_json_object_with_options(
+        mut object: JsonObject,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
         match util::expect_type(&mut object)? {
             ref type_ if type_ == "Topology" => Ok(Topology {
                 bbox: util::get_bbox(&mut object)?,
-                objects: util::get_objects(&mut object)?,
-                transform: util::get_scale_translate(&mut object)?,
-                arcs: util::get_arcs_position(&mut object)?,
+                objects: util::get_objects(&mut object, options)?,
+                transform: util::get_scale_translate(&mut object, options)?,
+                arcs: util::get_arcs_position(&mut object, options)?,
                 foreign_members: util::get_foreign_members(object)?,
             }),
             type_ => Err(Error::ExpectedType {
@@ -136,6 +194,148 @@ impl Topology {
         }
     }
 
+    /// Serialize the topology to a JSON string, with its members in the given order.
+    pub fn to_string_with_member_order(&self, order: MemberOrder) -> String {
+        let mut map = JsonObject::from(self);
+        match order {
+            MemberOrder::Canonical => serde_json::to_string(&map).unwrap(),
+            MemberOrder::Mapshaper => {
+                let mut members = Vec::with_capacity(map.len());
+                for key in [
+                    member_names::TYPE,
+                    member_names::TRANSFORM,
+                    member_names::BBOX,
+                    member_names::OBJECTS,
+                    member_names::ARCS,
+                ] {
+                    if let Some(value) = map.remove(key) {
+                        members.push((key.to_owned(), value));
+                    }
+                }
+                members.extend(map);
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|(key, value)| {
+                        format!(
+                            "{}:{}",
+                            serde_json::to_string(key).unwrap(),
+                            serde_json::to_string(value).unwrap()
+                        )
+                    })
+                    .collect();
+                format!("{{{}}}", members.join(","))
+            }
+        }
+    }
+
+    /// Serialize the topology to a JSON string, with the members of 'objects'
+    /// sorted by name.
+    ///
+    /// Objects are otherwise written in the order of `self.objects` when the
+    /// `preserve_order` feature of serde_json is enabled (by any crate of the
+    /// dependency graph), so this gives a stable output in all cases.
+    pub fn to_string_sorted_objects(&self) -> String {
+        let mut map = JsonObject::from(self);
+        let mut named_geoms: Vec<&NamedGeometry> = self.objects.iter().collect();
+        named_geoms.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut objects = JsonObject::new();
+        for named_geom in named_geoms {
+            objects.insert(
+                named_geom.name.clone(),
+                serde_json::to_value(&named_geom.geometry).unwrap(),
+            );
+        }
+        map.insert(
+            String::from(member_names::OBJECTS),
+            JsonValue::Object(objects),
+        );
+        serde_json::to_string(&map).unwrap()
+    }
+
+    /// Serialize the topology to a JSON string, with the arc positions and the point
+    /// coordinates rounded to `decimals` decimals (at most 15) to shrink the output.
+    ///
+    /// The bbox and transform are written as is. The arcs of a quantized topology
+    /// only contain integers, which are not affected.
+    pub fn to_string_with_precision(&self, decimals: u32) -> Result<String, Error> {
+        if decimals > 15 {
+            return Err(Error::InvalidPrecision(decimals));
+        }
+        let factor = 10f64.powi(decimals as i32);
+        let round = |pos: &mut Position| {
+            pos.iter_mut()
+                .for_each(|v| *v = (*v * factor).round() / factor)
+        };
+        let mut topo = self.clone();
+        topo.arcs.iter_mut().flatten().for_each(round);
+        for ng in topo.objects.iter_mut() {
+            ng.geometry.for_each_position_mut(&mut |pos| round(pos));
+        }
+        Ok(serde_json::to_string(&topo).unwrap())
+    }
+
+    /// Returns the top-level member `key` which isn't part of the TopoJSON
+    /// specification (such as vendor extensions), if any.
+    pub fn foreign_member(&self, key: &str) -> Option<&JsonValue> {
+        self.foreign_members.as_ref().and_then(|fm| fm.get(key))
+    }
+
+    /// Returns the 'crs' member of the topology, if any.
+    ///
+    /// This member isn't part of the TopoJSON specification, but some files
+    /// inherited it from GeoJSON. It is kept with the foreign members.
+    pub fn crs(&self) -> Option<&JsonValue> {
+        self.foreign_member("crs")
+    }
+
+    /// Returns the `index`-th arc as stored in the topology: when the topology has
+    /// a transform, its positions are quantized and delta-encoded.
+    pub fn raw_arc(&self, index: usize) -> Option<&Arc> {
+        self.arcs.get(index)
+    }
+
+    /// Returns the `index`-th arc decoded to real-world coordinates
+    /// (the transform, if any, being applied).
+    pub fn arc_coordinates(&self, index: usize) -> Option<Arc> {
+        self.arcs
+            .get(index)
+            .map(|arc| to_geojson::decode_arc(arc, self.transform))
+    }
+
+    /// Whether every coordinate of the arcs is an integer, as is the case for a
+    /// quantized topology: its arcs can then be written without a fractional part.
+    ///
+    /// Integer (`[4000, 0]`) and float (`[4000.0, 0.0]`) positions are read to the
+    /// same values, so this only depends on the coordinates and not on how they were written.
+    pub fn arcs_are_integral(&self) -> bool {
+        self.arcs
+            .iter()
+            .flatten()
+            .flatten()
+            .all(|v| v.is_finite() && v.fract() == 0.)
+    }
+
+    /// Number of arcs of the topology.
+    pub fn arc_count(&self) -> usize {
+        self.arcs.len()
+    }
+
+    /// Number of objects (named geometries) of the topology.
+    pub fn object_count(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Whether the 'objects' member of the topology is empty (`{}`), which is valid
+    /// (for example for a topology only carrying arcs) unlike a missing one.
+    pub fn is_empty_objects(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Whether the topology has neither objects nor arcs.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty() && self.arcs.is_empty()
+    }
+
     pub fn list_names(&self) -> Vec<String> {
         self.objects
             .iter()
@@ -143,6 +343,688 @@ impl Topology {
             .map(|g| g.name)
             .collect::<Vec<String>>()
     }
+
+    /// Check that the topology can be safely converted.
+    ///
+    /// Currently, this ensures that every position has at least two components
+    /// (see `Error::PositionTooShort`) and that no foreign member has the name of
+    /// a member of the specification (see `Error::ReservedForeignMemberKey`), as
+    /// those are left out when serializing.
+    pub fn validate(&self) -> Result<(), Error> {
+        util::validate_positions(self)?;
+        util::validate_foreign_members(self)
+    }
+
+    /// Check the topology against the constraints of the specification, returning
+    /// all the violations found (each distinct error being reported once) rather
+    /// than the first one.
+    ///
+    /// The 'type', 'arcs' and 'objects' members are always present in a parsed
+    /// topology. This checks that:
+    /// - the transform, if any, is finite (`Error::TransformNotFinite`),
+    /// - the object names are unique (`Error::DuplicateObjectName`),
+    /// - every position has at least two components (`Error::PositionTooShort`),
+    /// - every arc index references an arc (`Error::ArcIndexOutOfBounds`),
+    /// - the lines and rings of the geometries made of arcs reference at least
+    ///   one arc (`Error::EmptyArcIndexes`),
+    /// - no foreign member has the name of a member of the specification
+    ///   (`Error::ReservedForeignMemberKey`).
+    pub fn conformance_check(&self) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        let mut report = |error: Error| {
+            if !errors.contains(&error) {
+                errors.push(error);
+            }
+        };
+
+        if let Some(ref tr) = self.transform {
+            let values = [tr.scale[0], tr.scale[1], tr.translate[0], tr.translate[1]];
+            if let Err(e) = util::check_finite(&values, Error::TransformNotFinite) {
+                report(e);
+            }
+        }
+        let mut names = BTreeSet::new();
+        for named_geom in self.objects.iter() {
+            if !names.insert(named_geom.name.as_str()) {
+                report(Error::DuplicateObjectName(named_geom.name.clone()));
+            }
+        }
+
+        let check_position = |pos: &Position, report: &mut dyn FnMut(Error)| {
+            if pos.len() < 2 {
+                report(Error::PositionTooShort { found: pos.len() });
+            }
+        };
+        for pos in self.arcs.iter().flatten() {
+            check_position(pos, &mut report);
+        }
+        let check_foreign_members =
+            |foreign_members: &Option<JsonObject>,
+             reserved: &dyn Fn(&str) -> bool,
+             report: &mut dyn FnMut(Error)| {
+                for key in foreign_members.iter().flat_map(|fm| fm.keys()) {
+                    if reserved(key) {
+                        report(Error::ReservedForeignMemberKey(key.to_owned()));
+                    }
+                }
+            };
+        check_foreign_members(
+            &self.foreign_members,
+            &|key| member_names::TOPOLOGY_MEMBERS.contains(&key),
+            &mut report,
+        );
+
+        for named_geom in self.objects.iter() {
+            named_geom
+                .geometry
+                .for_each_position(&mut |pos| check_position(pos, &mut report));
+            named_geom.geometry.for_each_arc_index(&mut |ix| {
+                if util::normalize_arc_index(ix) >= self.arcs.len() {
+                    report(Error::ArcIndexOutOfBounds(ix as i64));
+                }
+            });
+            named_geom.geometry.for_each_geometry(&mut |g| {
+                let lines: Vec<&ArcIndexes> = match g.value {
+                    Value::LineString(ref ixs) => vec![ixs],
+                    Value::MultiLineString(ref lines) | Value::Polygon(ref lines) => {
+                        lines.iter().collect()
+                    }
+                    Value::MultiPolygon(ref polygons) => polygons.iter().flatten().collect(),
+                    _ => vec![],
+                };
+                if lines.iter().any(|ixs| ixs.is_empty()) {
+                    report(Error::EmptyArcIndexes);
+                }
+                check_foreign_members(
+                    &g.foreign_members,
+                    &|key| g.is_reserved_member(key),
+                    &mut report,
+                );
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Number of features that converting the `key` object to GeoJSON gives:
+    /// one per (non-collection) geometry, geometry collections being flattened.
+    pub fn feature_count(&self, key: &str) -> Result<usize, Error> {
+        self.objects
+            .iter()
+            .find(|ng| ng.name == key)
+            .map(|ng| count_features(&ng.geometry))
+            .ok_or_else(|| Error::TopoToGeoUnknownKey(key.to_owned()))
+    }
+
+    /// Number of features that converting every object to GeoJSON gives.
+    pub fn total_feature_count(&self) -> usize {
+        self.objects
+            .iter()
+            .map(|ng| count_features(&ng.geometry))
+            .sum()
+    }
+
+    /// The deepest nesting of geometry collections across all the objects: 0 when
+    /// there is no geometry collection, 1 for collections of plain geometries, etc.
+    pub fn max_collection_depth(&self) -> usize {
+        self.objects
+            .iter()
+            .map(|ng| collection_depth(&ng.geometry))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The set of the TopoJSON geometry types ('Point', 'GeometryCollection', etc.)
+    /// appearing in the objects, descending into geometry collections.
+    pub fn geometry_types(&self) -> BTreeSet<String> {
+        let mut types = BTreeSet::new();
+        for named_geom in self.objects.iter() {
+            named_geom.geometry.for_each_geometry(&mut |g| {
+                types.insert(g.value.type_name().to_owned());
+            });
+        }
+        types
+    }
+
+    /// Sort the objects by name, in place (the sort is stable, so objects sharing
+    /// a name keep their relative order).
+    ///
+    /// This only changes the order in which the objects are iterated and serialized:
+    /// arc indexes refer to `self.arcs`, so they are not affected.
+    pub fn sort_objects(&mut self) {
+        self.objects.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// Append an arc to the topology, returning its index (to be referenced by the
+    /// geometries of the objects).
+    ///
+    /// The arc is stored as is, so it must be quantized and delta-encoded if the
+    /// topology has a transform.
+    pub fn push_arc(&mut self, arc: Arc) -> usize {
+        self.arcs.push(arc);
+        self.arcs.len() - 1
+    }
+
+    /// Append a new object to the topology.
+    ///
+    /// Fails with [`Error::DuplicateObjectName`] if an object is already named `name`.
+    pub fn push_object(&mut self, name: String, geometry: Geometry) -> Result<(), Error> {
+        if self.objects.iter().any(|ng| ng.name == name) {
+            return Err(Error::DuplicateObjectName(name));
+        }
+        self.objects.push(NamedGeometry { name, geometry });
+        Ok(())
+    }
+
+    /// Replace the geometry of the `name` object, returning its previous geometry.
+    ///
+    /// If several objects have this name, only the first one is replaced. If there is
+    /// no such object, a new one is appended to the objects and `None` is returned.
+    pub fn set_object(&mut self, name: &str, geometry: Geometry) -> Option<Geometry> {
+        match self.objects.iter_mut().find(|ng| ng.name == name) {
+            Some(named_geom) => Some(std::mem::replace(&mut named_geom.geometry, geometry)),
+            None => {
+                self.objects.push(NamedGeometry {
+                    name: name.to_owned(),
+                    geometry,
+                });
+                None
+            }
+        }
+    }
+
+    /// Rename the object `from` to `to`.
+    ///
+    /// Fails with [`Error::TopoToGeoUnknownKey`] if there is no `from` object and with
+    /// [`Error::DuplicateObjectName`] if another object is already named `to`.
+    pub fn rename_object(&mut self, from: &str, to: &str) -> Result<(), Error> {
+        let ix = self
+            .objects
+            .iter()
+            .position(|ng| ng.name == from)
+            .ok_or_else(|| Error::TopoToGeoUnknownKey(from.to_owned()))?;
+        if from != to && self.objects.iter().any(|ng| ng.name == to) {
+            return Err(Error::DuplicateObjectName(to.to_owned()));
+        }
+        self.objects[ix].name = to.to_owned();
+        Ok(())
+    }
+
+    /// Split the topology into one self-contained topology per object.
+    ///
+    /// Each returned topology only carries the arcs referenced by its object,
+    /// in the order they are first referenced, and the arc indexes of the object
+    /// are rewritten accordingly (keeping their direction). The bbox, transform
+    /// and foreign members are kept as is.
+    ///
+    /// Fails with [`Error::ArcIndexOutOfBounds`] if an object references an arc
+    /// which is not in the topology.
+    pub fn split_by_object(&self) -> Result<Vec<(String, Topology)>, Error> {
+        self.check_arc_indexes()?;
+        Ok(self
+            .objects
+            .iter()
+            .map(|named_geom| {
+                let mut geometry = named_geom.geometry.clone();
+                let (arcs, _) = extract_arcs(&self.arcs, std::iter::once(&mut geometry));
+                let topo = Topology {
+                    bbox: self.bbox.clone(),
+                    objects: vec![NamedGeometry {
+                        name: named_geom.name.clone(),
+                        geometry,
+                    }],
+                    transform: self.transform,
+                    arcs,
+                    foreign_members: self.foreign_members.clone(),
+                };
+                (named_geom.name.clone(), topo)
+            })
+            .collect())
+    }
+
+    /// Fail with [`Error::ArcIndexOutOfBounds`] on the first arc index referencing
+    /// no arc, so that the arcs can then be looked up without bounds checks.
+    fn check_arc_indexes(&self) -> Result<(), Error> {
+        let mut out_of_bounds = None;
+        for named_geom in self.objects.iter() {
+            named_geom.geometry.for_each_arc_index(&mut |ix| {
+                if util::normalize_arc_index(ix) >= self.arcs.len() {
+                    out_of_bounds.get_or_insert(ix);
+                }
+            });
+        }
+        match out_of_bounds {
+            Some(ix) => Err(Error::ArcIndexOutOfBounds(ix as i64)),
+            None => Ok(()),
+        }
+    }
+
+    /// Convex hull of the decoded positions of the object `key`.
+    ///
+    /// This is a Polygon, unless the positions are all collinear: the hull is then
+    /// a LineString between the two extreme positions, or a Point if they are all
+    /// equal. An object without any position gives an empty GeometryCollection.
+    pub fn convex_hull(&self, key: &str) -> Result<geojson::Value, Error> {
+        let mut positions = Vec::new();
+        for feature in to_geojson::to_geojson(self, key)?.features {
+            if let Some(geometry) = feature.geometry {
+                algorithm::for_each_geojson_position(&geometry.value, &mut |pos| {
+                    positions.push(pos.clone())
+                });
+            }
+        }
+        let mut hull = algorithm::convex_hull(positions);
+        Ok(match hull.len() {
+            0 => geojson::Value::GeometryCollection(vec![]),
+            1 => geojson::Value::Point(hull.pop().unwrap()),
+            2 => geojson::Value::LineString(hull),
+            _ => {
+                hull.push(hull[0].clone());
+                geojson::Value::Polygon(vec![hull])
+            }
+        })
+    }
+
+    /// The area and perimeter of each Polygon and MultiPolygon feature obtained by
+    /// converting the `key` object to GeoJSON (in the order of the features, the
+    /// other features being skipped), computed in a single pass over the decoded rings.
+    ///
+    /// These are planar measures in the units of the coordinates. The first ring of
+    /// each polygon is its exterior ring, whatever its winding order.
+    pub fn polygon_metrics(&self, key: &str) -> Result<Vec<PolygonMetrics>, Error> {
+        let add_polygon = |metrics: &mut PolygonMetrics, rings: &[Vec<Position>]| {
+            for (i, ring) in rings.iter().enumerate() {
+                let area = algorithm::ring_area(ring).abs();
+                metrics.area += if i == 0 { area } else { -area };
+                metrics.perimeter += algorithm::ring_perimeter(ring);
+            }
+        };
+        let mut result = Vec::new();
+        for feature in to_geojson::to_geojson(self, key)?.features {
+            let mut metrics = PolygonMetrics {
+                area: 0.,
+                perimeter: 0.,
+            };
+            match feature.geometry.map(|g| g.value) {
+                Some(geojson::Value::Polygon(rings)) => add_polygon(&mut metrics, &rings),
+                Some(geojson::Value::MultiPolygon(polygons)) => polygons
+                    .iter()
+                    .for_each(|rings| add_polygon(&mut metrics, rings)),
+                _ => continue,
+            }
+            result.push(metrics);
+        }
+        Ok(result)
+    }
+
+    /// Return the (normalized) indexes of the arcs referenced by both the `key_a`
+    /// and `key_b` objects, in increasing order.
+    pub fn shared_arcs(&self, key_a: &str, key_b: &str) -> Result<Vec<usize>, Error> {
+        let arcs_a = self.object_arcs(key_a)?;
+        let arcs_b = self.object_arcs(key_b)?;
+        Ok(arcs_a.intersection(&arcs_b).copied().collect())
+    }
+
+    /// Count the arcs by number of references: each entry maps a number of references
+    /// to the number of arcs referenced that many times (by the geometries of all the
+    /// objects, an arc used twice by the same geometry counting twice).
+    ///
+    /// The arcs referenced by no geometry are counted in the `0` entry, and the
+    /// indexes referencing no arc are ignored.
+    pub fn arc_reuse_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut references = vec![0; self.arcs.len()];
+        for named_geom in self.objects.iter() {
+            named_geom.geometry.for_each_arc_index(&mut |ix| {
+                if let Some(count) = references.get_mut(util::normalize_arc_index(ix)) {
+                    *count += 1;
+                }
+            });
+        }
+        let mut histogram = BTreeMap::new();
+        for count in references {
+            *histogram.entry(count).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// The names of the objects referencing the arc at position `arc_index` (in either
+    /// direction), in the order of the objects.
+    pub fn objects_referencing_arc(&self, arc_index: usize) -> Vec<&str> {
+        self.objects
+            .iter()
+            .filter(|named_geom| {
+                let mut found = false;
+                named_geom.geometry.for_each_arc_index(&mut |ix| {
+                    found |= util::normalize_arc_index(ix) == arc_index;
+                });
+                found
+            })
+            .map(|named_geom| named_geom.name.as_str())
+            .collect()
+    }
+
+    /// The set of the (normalized) arc indexes referenced by the `key` object.
+    fn object_arcs(&self, key: &str) -> Result<BTreeSet<usize>, Error> {
+        let named_geom = self
+            .objects
+            .iter()
+            .find(|ng| ng.name == key)
+            .ok_or_else(|| Error::TopoToGeoUnknownKey(key.to_owned()))?;
+        let mut arcs = BTreeSet::new();
+        named_geom.geometry.for_each_arc_index(&mut |ix| {
+            arcs.insert(util::normalize_arc_index(ix));
+        });
+        Ok(arcs)
+    }
+
+    /// Remove the arcs which are not referenced by any object.
+    ///
+    /// The remaining arcs are ordered by first reference (following the order
+    /// of the objects and of the arc indexes within them), and the arc indexes
+    /// are rewritten accordingly. The result only depends on the content of the
+    /// topology, so processing the same topology twice gives identical output.
+    ///
+    /// Fails with [`Error::ArcIndexOutOfBounds`] (leaving the topology untouched)
+    /// if an object references an arc which is not in the topology.
+    pub fn prune_unused_arcs(&mut self) -> Result<(), Error> {
+        self.prune_unused_arcs_remap().map(|_| ())
+    }
+
+    /// Like [`Topology::prune_unused_arcs`], returning the remap table giving, for
+    /// each former arc, its new index (`None` if it was removed).
+    ///
+    /// An arc index `ix` stored elsewhere is translated by looking up the new index
+    /// of the arc `ix` (or `!ix` if negative), then complementing it if `ix` is negative.
+    pub fn prune_unused_arcs_remap(&mut self) -> Result<Vec<Option<i32>>, Error> {
+        self.check_arc_indexes()?;
+        let (arcs, remap) = extract_arcs(
+            &self.arcs,
+            self.objects
+                .iter_mut()
+                .map(|named_geom| &mut named_geom.geometry),
+        );
+        self.arcs = arcs;
+        Ok(remap)
+    }
+
+    /// Merge the arcs which are exactly equal (or the exact reverse of each other)
+    /// into a single arc, rewriting the arc indexes which referenced the duplicates.
+    ///
+    /// Arcs are compared on their absolute (delta-decoded) positions, bit-for-bit
+    /// (see [`arc_key`](crate::arc_key)). The first occurrence of an arc is kept, then
+    /// the arcs are pruned and ordered like [`Topology::prune_unused_arcs`] does, so the
+    /// result is deterministic.
+    ///
+    /// Fails with [`Error::ArcIndexOutOfBounds`] (leaving the topology untouched)
+    /// if an object references an arc which is not in the topology.
+    pub fn dedup_arcs(&mut self) -> Result<(), Error> {
+        self.dedup_arcs_remap().map(|_| ())
+    }
+
+    /// Like [`Topology::dedup_arcs`], returning the remap table giving, for each
+    /// former arc, its new index (`None` if it was removed). It is used like the
+    /// table of [`Topology::prune_unused_arcs_remap`], but the new index of a former
+    /// duplicate reversing the kept arc is negative (`!i` referencing the arc `i`).
+    pub fn dedup_arcs_remap(&mut self) -> Result<Vec<Option<i32>>, Error> {
+        self.check_arc_indexes()?;
+        let quantized = self.transform.is_some();
+        let absolute: Vec<Arc> = self
+            .arcs
+            .iter()
+            .map(|arc| undelta_arc(arc, quantized))
+            .collect();
+
+        // The arc index (possibly reversed) that should be used in place of each arc:
+        let mut replacements: Vec<i32> = (0..absolute.len() as i32).collect();
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (i, arc) in absolute.iter().enumerate() {
+            let forward = seen
+                .get(&util::arc_key(arc))
+                .and_then(|candidates| candidates.iter().find(|&&j| absolute[j] == *arc))
+                .map(|&j| j as i32);
+            let found = forward.or_else(|| {
+                seen.get(&util::arc_key_reversed(arc))
+                    .and_then(|candidates| {
+                        candidates
+                            .iter()
+                            .find(|&&j| absolute[j].iter().eq(arc.iter().rev()))
+                    })
+                    .map(|&j| !(j as i32))
+            });
+            match found {
+                Some(replacement) => replacements[i] = replacement,
+                None => seen.entry(util::arc_key(arc)).or_default().push(i),
+            }
+        }
+
+        for named_geom in self.objects.iter_mut() {
+            named_geom.geometry.for_each_arc_index_mut(&mut |ix| {
+                let replacement = replacements[util::normalize_arc_index(*ix)];
+                *ix = if *ix < 0 { !replacement } else { replacement };
+            });
+        }
+        let pruned = self.prune_unused_arcs_remap()?;
+        Ok(replacements
+            .into_iter()
+            .map(|replacement| {
+                pruned[util::normalize_arc_index(replacement)]
+                    .map(|new_ix| util::remap_arc_index(replacement, new_ix as usize))
+            })
+            .collect())
+    }
+
+    /// Remove, in every arc, the positions which are equal to the previous position.
+    ///
+    /// Only exact duplicates are removed (this is not a simplification) and every arc
+    /// keeps at least two positions. For quantized topologies, the duplicates are the
+    /// delta-encoded positions equal to `[0, 0]`, so the transform is left untouched.
+    pub fn remove_repeated_points(&mut self) {
+        let quantized = self.transform.is_some();
+        for arc in self.arcs.iter_mut() {
+            let first = match arc.first() {
+                Some(first) => first.clone(),
+                None => continue,
+            };
+            let mut result: Arc = Vec::with_capacity(arc.len());
+            result.push(first);
+            for pt in arc.iter().skip(1) {
+                let repeated = if quantized {
+                    pt.iter().take(2).all(|c| *c == 0.)
+                } else {
+                    Some(pt) == result.last()
+                };
+                if !repeated {
+                    result.push(pt.clone());
+                }
+            }
+            if result.len() < 2 && arc.len() >= 2 {
+                result.push(arc[arc.len() - 1].clone());
+            }
+            *arc = result;
+        }
+    }
+
+    /// Replace every reversed arc index (`~i`) by the index of a new arc, explicitly
+    /// storing the reverse of the arc `i`, so that the topology only contains positive
+    /// arc indexes.
+    ///
+    /// The reversed arcs are appended to the arcs (once per reversed arc, in order of
+    /// first reference) and the decoded geometries are left unchanged.
+    ///
+    /// Fails with [`Error::ArcIndexOutOfBounds`] (leaving the topology untouched)
+    /// if an object references an arc which is not in the topology.
+    pub fn materialize_reversed_arcs(&mut self) -> Result<(), Error> {
+        self.check_arc_indexes()?;
+        let quantized = self.transform.is_some();
+        let arcs = &mut self.arcs;
+        let mut reversed: HashMap<usize, usize> = HashMap::new();
+        for named_geom in self.objects.iter_mut() {
+            named_geom.geometry.for_each_arc_index_mut(&mut |ix| {
+                if *ix >= 0 {
+                    return;
+                }
+                let old_ix = util::normalize_arc_index(*ix);
+                let new_ix = *reversed.entry(old_ix).or_insert_with(|| {
+                    let mut arc = undelta_arc(&arcs[old_ix], quantized);
+                    arc.reverse();
+                    arcs.push(delta_arc(arc, quantized));
+                    arcs.len() - 1
+                });
+                *ix = new_ix as i32;
+            });
+        }
+        Ok(())
+    }
+
+    /// Use the value of the property `key` as the id of the geometries having no id.
+    ///
+    /// Every geometry (including the geometry collections and their members) having no
+    /// id but having the property `key` gets this value as id, and the property is
+    /// removed. Geometries which already have an id are left untouched.
+    pub fn promote_id_from_property(&mut self, key: &str) {
+        for named_geom in self.objects.iter_mut() {
+            named_geom.geometry.for_each_geometry_mut(&mut |geometry| {
+                if geometry.id.is_none() {
+                    geometry.id = geometry.remove_property(key);
+                }
+            });
+        }
+    }
+
+    /// Apply `f` to the properties of every geometry (including the geometry
+    /// collections and their members), e.g. to rename or drop some of them.
+    ///
+    /// Geometries without properties are given an empty map (so that `f` can add
+    /// some), and the properties left empty by `f` are removed.
+    pub fn map_properties<F: FnMut(&mut JsonObject)>(&mut self, mut f: F) {
+        for named_geom in self.objects.iter_mut() {
+            named_geom.geometry.for_each_geometry_mut(&mut |geometry| {
+                let mut properties = geometry.properties.take().unwrap_or_default();
+                f(&mut properties);
+                if !properties.is_empty() {
+                    geometry.properties = Some(properties);
+                }
+            });
+        }
+    }
+
+    /// Compare the shapes described by two topologies.
+    ///
+    /// Objects are matched by name and their decoded features are compared in order:
+    /// geometry types must be the same and coordinates may differ by at most `epsilon`.
+    /// Unlike `PartialEq`, this doesn't depend on how the shapes are split into arcs
+    /// nor on the transform used, so repeated positions at arc joins are ignored and
+    /// rings may start from a different position. Properties and ids are not compared.
+    pub fn approx_eq(&self, other: &Topology, epsilon: f64) -> bool {
+        if self.objects.len() != other.objects.len() {
+            return false;
+        }
+        self.objects.iter().all(|named_geom| {
+            let (a, b) = match (
+                to_geojson(self, &named_geom.name),
+                to_geojson(other, &named_geom.name),
+            ) {
+                (Ok(a), Ok(b)) => (a, b),
+                _ => return false,
+            };
+            a.features.len() == b.features.len()
+                && a.features.iter().zip(b.features.iter()).all(|(f, g)| {
+                    match (&f.geometry, &g.geometry) {
+                        (Some(u), Some(v)) => {
+                            algorithm::geom_values_approx_eq(&u.value, &v.value, epsilon)
+                        }
+                        (None, None) => true,
+                        _ => false,
+                    }
+                })
+        })
+    }
+}
+
+fn count_features(geometry: &Geometry) -> usize {
+    match geometry.value {
+        Value::GeometryCollection(ref geoms) => geoms.iter().map(count_features).sum(),
+        // Skipped when converting to GeoJSON:
+        Value::Unknown(..) => 0,
+        _ => 1,
+    }
+}
+
+fn collection_depth(geometry: &Geometry) -> usize {
+    match geometry.value {
+        Value::GeometryCollection(ref geoms) => {
+            1 + geoms.iter().map(collection_depth).max().unwrap_or(0)
+        }
+        _ => 0,
+    }
+}
+
+/// Copy the arcs referenced by `geometries`, in the order they are first referenced,
+/// and rewrite their arc indexes to point into the returned arcs.
+///
+/// Also returns the new index of each of the `arcs` (`None` if it isn't referenced).
+fn extract_arcs<'a, I>(arcs: &[Arc], geometries: I) -> (Vec<Arc>, Vec<Option<i32>>)
+where
+    I: IntoIterator<Item = &'a mut Geometry>,
+{
+    let mut new_arcs = Vec::new();
+    let mut remap: Vec<Option<i32>> = vec![None; arcs.len()];
+    for geometry in geometries {
+        geometry.for_each_arc_index_mut(&mut |ix| {
+            let old_ix = util::normalize_arc_index(*ix);
+            let new_ix = *remap[old_ix].get_or_insert_with(|| {
+                new_arcs.push(arcs[old_ix].clone());
+                new_arcs.len() as i32 - 1
+            });
+            *ix = util::remap_arc_index(*ix, new_ix as usize);
+        });
+    }
+    (new_arcs, remap)
+}
+
+/// Delta-encode the absolute positions of an arc if the arc is quantized
+/// (positions with less than two components are kept as they are).
+fn delta_arc(mut arc: Arc, quantized: bool) -> Arc {
+    if quantized {
+        let (mut x, mut y) = (0., 0.);
+        for pt in arc.iter_mut() {
+            if pt.len() < 2 {
+                continue;
+            }
+            let (abs_x, abs_y) = (pt[0], pt[1]);
+            pt[0] -= x;
+            pt[1] -= y;
+            x = abs_x;
+            y = abs_y;
+        }
+    }
+    arc
+}
+
+/// Get the absolute positions of an arc, undoing the delta-encoding
+/// (but not the transform) if the arc is quantized.
+fn undelta_arc(arc: &[crate::Position], quantized: bool) -> Arc {
+    if !quantized {
+        return arc.to_vec();
+    }
+    let (mut x, mut y) = (0., 0.);
+    arc.iter()
+        .map(|pt| {
+            let mut new_pt = pt.clone();
+            if pt.len() < 2 {
+                return new_pt;
+            }
+            x += pt[0];
+            y += pt[1];
+            new_pt[0] = x;
+            new_pt[1] = y;
+            new_pt
+        })
+        .collect()
 }
 
 impl Serialize for Topology {
@@ -161,9 +1043,19 @@ impl<'de> Deserialize<'de> for Topology {
     {
         use serde::de::Error as SerdeError;
 
-        let val = JsonObject::deserialize(deserializer)?;
+        let val = util::OrderedObject::deserialize(deserializer)?;
 
-        Topology::from_json_object(val).map_err(D::Error::custom)
+        let mut topo = Topology::from_json_object(val.object).map_err(D::Error::custom)?;
+        util::restore_objects_order(&mut topo.objects, &val.objects_order);
+        Ok(topo)
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        ::serde_json::to_string(self)
+            .map_err(|_| fmt::Error)
+            .and_then(|s| f.write_str(&s))
     }
 }
 
@@ -178,8 +1070,11 @@ impl From<TopoJson> for Option<Topology> {
 
 #[cfg(test)]
 mod tests {
-    use crate::json::JsonObject;
-    use crate::{Error, Geometry, NamedGeometry, TopoJson, Topology, TransformParams, Value};
+    use crate::json::{JsonObject, JsonValue};
+    use crate::{
+        to_feature, to_geojson, to_geojson_all, Arc, Error, Geometry, MemberOrder, NamedGeometry,
+        ParseOptions, PolygonMetrics, TopoJson, Topology, TransformParams, Value,
+    };
 
     fn encode(topo: &Topology) -> String {
         serde_json::to_string(&topo).unwrap()
@@ -213,6 +1108,21 @@ mod tests {
         assert_eq!(decoded_topo, topo);
     }
 
+    #[test]
+    fn decode_topology_empty_objects() {
+        let topo_json_str =
+            "{\"arcs\":[[[2.2,2.2],[3.3,3.3]]],\"objects\":{},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        assert!(topo.is_empty_objects());
+        assert!(!topo.is_empty());
+        assert!(topo.list_names().is_empty());
+        assert!(to_geojson_all(&topo).unwrap().is_empty());
+        assert_eq!(encode(&topo), topo_json_str);
+    }
+
     #[test]
     fn decode_invalid_topology_no_objects() {
         let topo_json_str = "{\"arcs\":[[[2.2,2.2],[3.3,3.3]]],\"type\":\"Topology\"}";
@@ -481,4 +1391,1104 @@ mod tests {
         };
         assert_eq!(decoded_topo, topo);
     }
+
+    #[test]
+    fn default_topology() {
+        let topo = Topology {
+            arcs: vec![vec![vec![2.2, 2.2], vec![3.3, 3.3]]],
+            ..Default::default()
+        };
+        assert!(topo.objects.is_empty());
+        assert_eq!(topo.bbox, None);
+        assert_eq!(topo.transform, None);
+        assert_eq!(topo.foreign_members, None);
+        assert_eq!(
+            encode(&topo),
+            "{\"arcs\":[[[2.2,2.2],[3.3,3.3]]],\"objects\":{},\"type\":\"Topology\"}"
+        );
+    }
+
+    #[test]
+    fn counts_and_emptiness() {
+        let topo = Topology::default();
+        assert_eq!((topo.arc_count(), topo.object_count()), (0, 0));
+        assert!(topo.is_empty());
+
+        let topo_json_str = "{\"arcs\":[[[2.2,2.2],[3.3,3.3]]],\"objects\":{\"example\":{\"arcs\":[0],\"type\":\"LineString\"}},\"type\":\"Topology\"}";
+        let mut topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!((topo.arc_count(), topo.object_count()), (1, 1));
+        assert!(!topo.is_empty());
+        topo.objects.clear();
+        assert!(!topo.is_empty());
+    }
+
+    #[test]
+    fn split_by_object() {
+        let topo_json_str = "{\"arcs\":[[[0,0],[1,0]],[[1,0],[1,1]],[[1,1],[0,1],[0,0]],[[5,5],[6,6]]],\"objects\":{\"line\":{\"arcs\":[3],\"type\":\"LineString\"},\"square\":{\"geometries\":[{\"arcs\":[[2,0,1]],\"type\":\"Polygon\"},{\"arcs\":[-2],\"type\":\"LineString\"}],\"type\":\"GeometryCollection\"}},\"transform\":{\"scale\":[0.5,0.5],\"translate\":[10,10]},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let parts = topo.split_by_object().unwrap();
+        assert_eq!(parts.len(), 2);
+        for (name, part) in parts.iter() {
+            assert_eq!(part.list_names(), vec![name.clone()]);
+            assert_eq!(part.transform, topo.transform);
+            assert_eq!(to_geojson(part, name), to_geojson(&topo, name));
+        }
+
+        let (_, line) = &parts[0];
+        assert_eq!(line.arcs, vec![topo.arcs[3].clone()]);
+        assert_eq!(line.objects[0].geometry.value, Value::LineString(vec![0]));
+
+        let (_, square) = &parts[1];
+        assert_eq!(square.arcs.len(), 3);
+        assert_eq!(square.arcs[0], topo.arcs[2]);
+        match square.objects[0].geometry.value {
+            Value::GeometryCollection(ref geoms) => {
+                assert_eq!(geoms[0].value, Value::Polygon(vec![vec![0, 1, 2]]));
+                assert_eq!(geoms[1].value, Value::LineString(vec![-3]));
+            }
+            _ => panic!(),
+        }
+
+        let mut invalid = topo.clone();
+        invalid.objects[0].geometry.value = Value::LineString(vec![5]);
+        assert_eq!(
+            invalid.split_by_object().err(),
+            Some(Error::ArcIndexOutOfBounds(5))
+        );
+    }
+
+    #[test]
+    fn prune_unused_arcs_is_deterministic() {
+        let topo_json_str = "{\"arcs\":[[[9,9],[8,8]],[[0,0],[1,0]],[[1,0],[1,1]],[[7,7],[6,6]],[[1,1],[0,0]]],\"objects\":{\"a\":{\"arcs\":[[4,1,2]],\"type\":\"Polygon\"},\"b\":{\"arcs\":[-3],\"type\":\"LineString\"}},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let mut first = topo.clone();
+        first.prune_unused_arcs().unwrap();
+        let mut second = topo.clone();
+        second.prune_unused_arcs().unwrap();
+        assert_eq!(first, second);
+        assert_eq!(encode(&first), encode(&second));
+
+        // Arcs are ordered by first reference:
+        assert_eq!(
+            first.arcs,
+            vec![
+                topo.arcs[4].clone(),
+                topo.arcs[1].clone(),
+                topo.arcs[2].clone()
+            ]
+        );
+        assert_eq!(
+            first.objects[0].geometry.value,
+            Value::Polygon(vec![vec![0, 1, 2]])
+        );
+        assert_eq!(first.objects[1].geometry.value, Value::LineString(vec![-3]));
+        for name in ["a", "b"] {
+            assert_eq!(to_geojson(&first, name), to_geojson(&topo, name));
+        }
+    }
+
+    #[test]
+    fn dedup_arcs_merges_reversed_arcs() {
+        // Arc 1 is the reverse of arc 0 and arc 2 is a copy of arc 0 (once delta-decoded):
+        let topo_json_str = "{\"arcs\":[[[0,0],[2,0],[0,3]],[[2,3],[0,-3],[-2,0]],[[0,0],[2,0],[0,3]]],\"objects\":{\"a\":{\"arcs\":[0,1],\"type\":\"LineString\"},\"b\":{\"arcs\":[[-2,2]],\"type\":\"MultiLineString\"}},\"transform\":{\"scale\":[1,1],\"translate\":[0,0]},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let mut deduped = topo.clone();
+        deduped.dedup_arcs().unwrap();
+        assert_eq!(deduped.arcs, vec![topo.arcs[0].clone()]);
+        assert_eq!(
+            deduped.objects[0].geometry.value,
+            Value::LineString(vec![0, -1])
+        );
+        assert_eq!(
+            deduped.objects[1].geometry.value,
+            Value::MultiLineString(vec![vec![0, 0]])
+        );
+        for name in ["a", "b"] {
+            assert_eq!(to_geojson(&deduped, name), to_geojson(&topo, name));
+        }
+
+        let mut again = topo.clone();
+        again.dedup_arcs().unwrap();
+        assert_eq!(encode(&deduped), encode(&again));
+    }
+
+    #[test]
+    fn arc_remap_tables() {
+        let translate = |remap: &[Option<i32>], ix: i32| {
+            remap[crate::util::normalize_arc_index(ix)]
+                .map(|new_ix| if ix < 0 { !new_ix } else { new_ix })
+        };
+
+        let topo_json_str = "{\"arcs\":[[[9,9],[8,8]],[[0,0],[1,0]],[[1,0],[1,1]]],\"objects\":{\"a\":{\"arcs\":[2,1],\"type\":\"LineString\"}},\"type\":\"Topology\"}";
+        let mut topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+        let remap = topo.prune_unused_arcs_remap().unwrap();
+        assert_eq!(remap, vec![None, Some(1), Some(0)]);
+        // A reference to the reversed arc 2, stored before pruning:
+        assert_eq!(translate(&remap, -3), Some(-1));
+        assert_eq!(topo.arcs[0], vec![vec![1., 0.], vec![1., 1.]]);
+
+        // Arc 1 is the reverse of arc 0 and arc 2 is a copy of arc 0:
+        let topo_json_str = "{\"arcs\":[[[0,0],[2,0],[0,3]],[[2,3],[0,-3],[-2,0]],[[0,0],[2,0],[0,3]]],\"objects\":{\"a\":{\"arcs\":[0,1],\"type\":\"LineString\"},\"b\":{\"arcs\":[[-2,2]],\"type\":\"MultiLineString\"}},\"transform\":{\"scale\":[1,1],\"translate\":[0,0]},\"type\":\"Topology\"}";
+        let mut topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+        let remap = topo.dedup_arcs_remap().unwrap();
+        assert_eq!(remap, vec![Some(0), Some(-1), Some(0)]);
+        assert_eq!(translate(&remap, 1), Some(-1));
+        assert_eq!(translate(&remap, -2), Some(0));
+
+        // Out-of-bounds arc indexes are reported, leaving the topology untouched:
+        topo.objects[0].geometry.value = Value::LineString(vec![5]);
+        let before = topo.clone();
+        assert_eq!(
+            topo.prune_unused_arcs_remap(),
+            Err(Error::ArcIndexOutOfBounds(5))
+        );
+        assert_eq!(topo.dedup_arcs(), Err(Error::ArcIndexOutOfBounds(5)));
+        assert_eq!(topo, before);
+    }
+
+    #[test]
+    fn approx_eq_ignores_arc_splits() {
+        // A square and a line, made of a single arc each:
+        let topo_a = decode("{\"arcs\":[[[0,0],[1,0],[1,1],[0,1],[0,0]],[[0,0],[2,0],[3,1]]],\"objects\":{\"line\":{\"arcs\":[1],\"type\":\"LineString\"},\"square\":{\"arcs\":[[0]],\"type\":\"Polygon\"}},\"type\":\"Topology\"}".to_string())
+            .try_unwrap_topology()
+            .unwrap();
+        // The same shapes split into several arcs, in a quantized topology:
+        let topo_b = decode("{\"arcs\":[[[2,0],[0,2]],[[2,2],[-2,0],[0,-2]],[[0,0],[2,0]],[[0,0],[4,0],[2,2]]],\"objects\":{\"square\":{\"arcs\":[[0,1,2]],\"type\":\"Polygon\"},\"line\":{\"arcs\":[3],\"type\":\"LineString\"}},\"transform\":{\"scale\":[0.5,0.5],\"translate\":[0,0]},\"type\":\"Topology\"}".to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        assert_ne!(topo_a, topo_b);
+        assert!(topo_a.approx_eq(&topo_b, 1e-9));
+        assert!(topo_b.approx_eq(&topo_a, 1e-9));
+
+        let mut moved = topo_b.clone();
+        moved.arcs[3][2] = vec![2.0, 2.1];
+        assert!(!topo_a.approx_eq(&moved, 1e-9));
+        assert!(topo_a.approx_eq(&moved, 0.1));
+    }
+
+    #[test]
+    fn to_string_with_member_order() {
+        let topo_json_str = "{\"arcs\":[[[2.2,2.2],[3.3,3.3]]],\"bbox\":[2.2,2.2,3.3,3.3],\"objects\":{\"example\":{\"arcs\":[0],\"type\":\"LineString\"}},\"other_member\":true,\"transform\":{\"scale\":[0.12,0.12],\"translate\":[1.1,1.1]},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        assert_eq!(
+            topo.to_string_with_member_order(MemberOrder::Canonical),
+            encode(&topo)
+        );
+        let mapshaper = topo.to_string_with_member_order(MemberOrder::Mapshaper);
+        assert_eq!(
+            mapshaper,
+            "{\"type\":\"Topology\",\"transform\":{\"scale\":[0.12,0.12],\"translate\":[1.1,1.1]},\"bbox\":[2.2,2.2,3.3,3.3],\"objects\":{\"example\":{\"arcs\":[0],\"type\":\"LineString\"}},\"arcs\":[[[2.2,2.2],[3.3,3.3]]],\"other_member\":true}"
+        );
+        assert_eq!(decode(mapshaper).try_unwrap_topology().unwrap(), topo);
+    }
+
+    #[test]
+    fn remove_repeated_points() {
+        let mut topo = Topology {
+            arcs: vec![
+                vec![vec![0.0, 0.0], vec![0.0, 0.0], vec![1.0, 1.0]],
+                vec![vec![2.0, 2.0], vec![2.0, 2.0], vec![2.0, 2.0]],
+                vec![vec![0.0, 0.0], vec![1.0, 1.0], vec![0.0, 0.0]],
+            ],
+            ..Default::default()
+        };
+        topo.remove_repeated_points();
+        assert_eq!(
+            topo.arcs,
+            vec![
+                vec![vec![0.0, 0.0], vec![1.0, 1.0]],
+                vec![vec![2.0, 2.0], vec![2.0, 2.0]],
+                vec![vec![0.0, 0.0], vec![1.0, 1.0], vec![0.0, 0.0]],
+            ]
+        );
+
+        // In quantized topologies, repeated positions are encoded as [0, 0]:
+        let mut topo = Topology {
+            arcs: vec![vec![
+                vec![0.0, 0.0],
+                vec![2.0, 2.0],
+                vec![0.0, 0.0],
+                vec![1.0, -1.0],
+            ]],
+            transform: Some(TransformParams {
+                scale: [0.5, 0.5],
+                translate: [0.0, 0.0],
+            }),
+            ..Default::default()
+        };
+        topo.remove_repeated_points();
+        assert_eq!(
+            topo.arcs,
+            vec![vec![vec![0.0, 0.0], vec![2.0, 2.0], vec![1.0, -1.0]]]
+        );
+    }
+
+    #[test]
+    fn materialize_reversed_arcs() {
+        let topo_json_str = "{\"arcs\":[[[0,0],[2,0],[0,2]],[[2,2],[-2,0],[0,-2]]],\"objects\":{\"square\":{\"arcs\":[[0,1]],\"type\":\"Polygon\"},\"lines\":{\"arcs\":[[-1],[-2,-1]],\"type\":\"MultiLineString\"}},\"transform\":{\"scale\":[0.5,0.5],\"translate\":[1,1]},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let mut materialized = topo.clone();
+        materialized.materialize_reversed_arcs().unwrap();
+        assert_eq!(materialized.arcs.len(), 4);
+        assert_eq!(
+            materialized.arcs[2],
+            vec![vec![2.0, 2.0], vec![0.0, -2.0], vec![-2.0, 0.0]]
+        );
+        assert_eq!(
+            materialized.objects[1].geometry.value,
+            Value::MultiLineString(vec![vec![2], vec![3, 2]])
+        );
+        for name in ["square", "lines"] {
+            assert_eq!(to_geojson(&materialized, name), to_geojson(&topo, name));
+        }
+
+        let mut out_of_bounds = topo.clone();
+        out_of_bounds.objects[1].geometry.value = Value::LineString(vec![-4]);
+        let before = out_of_bounds.clone();
+        assert_eq!(
+            out_of_bounds.materialize_reversed_arcs(),
+            Err(Error::ArcIndexOutOfBounds(-4))
+        );
+        assert_eq!(out_of_bounds, before);
+    }
+
+    #[test]
+    fn promote_id_from_property() {
+        let topo_json_str = "{\"arcs\":[],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[0,0],\"properties\":{\"code\":\"FR\",\"name\":\"France\"},\"type\":\"Point\"},{\"coordinates\":[1,1],\"id\":\"DE\",\"properties\":{\"code\":\"XX\"},\"type\":\"Point\"},{\"coordinates\":[2,2],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}";
+        let mut topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+        topo.promote_id_from_property("code");
+
+        let geoms = match topo.objects[0].geometry.value {
+            Value::GeometryCollection(ref geoms) => geoms,
+            _ => unreachable!(),
+        };
+        assert_eq!(geoms[0].id, Some(serde_json::to_value("FR").unwrap()));
+        assert_eq!(geoms[0].property("code"), None);
+        assert!(geoms[0].property("name").is_some());
+        // Existing ids are kept:
+        assert_eq!(geoms[1].id, Some(serde_json::to_value("DE").unwrap()));
+        assert!(geoms[1].property("code").is_some());
+        assert_eq!(geoms[2].id, None);
+    }
+
+    #[test]
+    fn map_properties() {
+        let topo_json_str = "{\"arcs\":[],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[0,0],\"properties\":{\"name\":\"a\",\"secret\":1},\"type\":\"Point\"},{\"geometries\":[{\"coordinates\":[1,1],\"properties\":{\"secret\":2},\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}],\"type\":\"GeometryCollection\"},\"point\":{\"coordinates\":[2,2],\"properties\":{\"secret\":3},\"type\":\"Point\"}},\"type\":\"Topology\"}";
+        let mut topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+        topo.map_properties(|properties| {
+            properties.remove("secret");
+        });
+        assert_eq!(
+            encode(&topo),
+            "{\"arcs\":[],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[0.0,0.0],\"properties\":{\"name\":\"a\"},\"type\":\"Point\"},{\"geometries\":[{\"coordinates\":[1.0,1.0],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}],\"type\":\"GeometryCollection\"},\"point\":{\"coordinates\":[2.0,2.0],\"type\":\"Point\"}},\"type\":\"Topology\"}"
+        );
+
+        // Geometries without properties can be given some:
+        topo.map_properties(|properties| {
+            properties.insert(String::from("source"), JsonValue::from("osm"));
+        });
+        assert_eq!(
+            topo.objects[1].geometry.property("source"),
+            Some(&JsonValue::from("osm"))
+        );
+    }
+
+    #[test]
+    fn decode_topology_with_crs() {
+        let topo_json_str = "{\"arcs\":[],\"crs\":{\"properties\":{\"name\":\"urn:ogc:def:crs:EPSG::2154\"},\"type\":\"name\"},\"objects\":{},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let crs = topo.crs().expect("No crs member");
+        assert_eq!(crs["properties"]["name"], "urn:ogc:def:crs:EPSG::2154");
+        assert_eq!(encode(&topo), topo_json_str);
+
+        assert_eq!(Topology::default().crs(), None);
+    }
+
+    #[test]
+    fn decode_invalid_topology_non_finite_transform() {
+        // Out of range numbers are already rejected when reading the JSON (as
+        // malformed, or as non-numeric with the 'arbitrary-precision' feature):
+        let topo_json_str = "{\"arcs\":[],\"objects\":{},\"transform\":{\"scale\":[1e400,0.5],\"translate\":[0,0]},\"type\":\"Topology\"}";
+        assert!(topo_json_str.parse::<TopoJson>().is_err());
+
+        // But non-finite values can be written as strings:
+        let options = ParseOptions {
+            numeric_strings: true,
+            ..Default::default()
+        };
+        for (scale, translate) in [
+            ("\"NaN\",0.5", "0,0"),
+            ("1,1", "\"-inf\",0"),
+            ("\"Infinity\",1", "0,0"),
+        ] {
+            let topo_json_str = format!("{{\"arcs\":[],\"objects\":{{}},\"transform\":{{\"scale\":[{}],\"translate\":[{}]}},\"type\":\"Topology\"}}", scale, translate);
+            assert_eq!(
+                TopoJson::from_str_with_options(&topo_json_str, &options).err(),
+                Some(Error::TransformNotFinite)
+            );
+        }
+    }
+
+    #[test]
+    fn shared_arcs() {
+        let topo_json_str = "{\"arcs\":[[[0,0],[1,0]],[[1,0],[1,1]],[[1,1],[0,1],[0,0]],[[5,5],[6,6]]],\"objects\":{\"land\":{\"arcs\":[[0,1,2]],\"type\":\"Polygon\"},\"maritime\":{\"geometries\":[{\"arcs\":[-2],\"type\":\"LineString\"},{\"arcs\":[3],\"type\":\"LineString\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        assert_eq!(topo.shared_arcs("land", "maritime"), Ok(vec![1]));
+        assert_eq!(topo.shared_arcs("land", "land"), Ok(vec![0, 1, 2]));
+        assert_eq!(
+            topo.shared_arcs("land", "foo"),
+            Err(Error::TopoToGeoUnknownKey("foo".to_string()))
+        );
+    }
+
+    #[test]
+    fn polygon_metrics() {
+        // A unit square (with the line along its side being skipped) and a
+        // 3 × 3 square with a unit hole:
+        let topo_json_str = "{\"arcs\":[[[0,0],[1,0],[1,1],[0,1],[0,0]],[[0,0],[3,0],[3,3],[0,3],[0,0]],[[1,1],[1,2],[2,2],[2,1],[1,1]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[[0]],\"type\":\"Polygon\"},{\"arcs\":[0],\"type\":\"LineString\"},{\"arcs\":[[[1],[2]]],\"type\":\"MultiPolygon\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        assert_eq!(
+            topo.polygon_metrics("example"),
+            Ok(vec![
+                PolygonMetrics {
+                    area: 1.,
+                    perimeter: 4.
+                },
+                PolygonMetrics {
+                    area: 8.,
+                    perimeter: 16.
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn build_arc_by_arc() {
+        let mut topo = Topology {
+            arcs: vec![],
+            objects: vec![],
+            bbox: None,
+            transform: None,
+            foreign_members: None,
+        };
+        let first = topo.push_arc(vec![vec![0., 0.], vec![1., 0.]]);
+        let second = topo.push_arc(vec![vec![1., 0.], vec![1., 1.]]);
+        assert_eq!((first, second), (0, 1));
+
+        let line = Geometry::new(Value::LineString(vec![first as i32, second as i32]));
+        assert_eq!(topo.push_object(String::from("road"), line.clone()), Ok(()));
+        assert_eq!(
+            topo.push_object(String::from("road"), line),
+            Err(Error::DuplicateObjectName(String::from("road")))
+        );
+        assert_eq!(topo.objects.len(), 1);
+
+        let feature = to_feature(&topo, "road").unwrap();
+        assert_eq!(
+            feature.geometry.unwrap().value,
+            geojson::Value::LineString(vec![vec![0., 0.], vec![1., 0.], vec![1., 1.]])
+        );
+    }
+
+    #[test]
+    fn conformance_check() {
+        let topo_json_str = "{\"arcs\":[[[0,0],[1,0]],[[1,0],[1,1]]],\"objects\":{\"land\":{\"arcs\":[[0,1]],\"type\":\"Polygon\"},\"roads\":{\"arcs\":[-2],\"type\":\"LineString\"}},\"transform\":{\"scale\":[1,1],\"translate\":[0,0]},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(topo.conformance_check(), Ok(()));
+
+        // Out of bounds indexes and an empty line:
+        let mut broken = topo.clone();
+        broken.objects[0].geometry.value = Value::Polygon(vec![vec![0, 2, -4], vec![]]);
+        assert_eq!(
+            broken.conformance_check(),
+            Err(vec![
+                Error::ArcIndexOutOfBounds(2),
+                Error::ArcIndexOutOfBounds(-4),
+                Error::EmptyArcIndexes,
+            ])
+        );
+
+        // Each distinct error is reported once, whatever the number of occurrences:
+        let mut broken = topo;
+        broken.transform.as_mut().unwrap().scale[0] = f64::NAN;
+        broken.objects[1].name = String::from("land");
+        broken.arcs[0] = vec![vec![0.], vec![1.]];
+        broken.objects[1].geometry.value = Value::LineString(vec![]);
+        assert_eq!(
+            broken.conformance_check(),
+            Err(vec![
+                Error::TransformNotFinite,
+                Error::DuplicateObjectName(String::from("land")),
+                Error::PositionTooShort { found: 1 },
+                Error::EmptyArcIndexes,
+            ])
+        );
+    }
+
+    #[test]
+    fn objects_referencing_arc() {
+        // Two squares sharing the arc 0 (the second one using it reversed):
+        let topo_json_str = "{\"arcs\":[[[1,0],[1,1]],[[1,1],[0,1],[0,0],[1,0]],[[1,0],[2,0],[2,1],[1,1]]],\"objects\":{\"west\":{\"arcs\":[[0,1]],\"type\":\"Polygon\"},\"east\":{\"arcs\":[[2,-1]],\"type\":\"Polygon\"}},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        assert_eq!(topo.objects_referencing_arc(0), vec!["west", "east"]);
+        assert_eq!(topo.objects_referencing_arc(2), vec!["east"]);
+        assert!(topo.objects_referencing_arc(3).is_empty());
+    }
+
+    #[test]
+    fn arc_reuse_histogram() {
+        // The arc 1 is shared by the two objects, the arc 3 isn't used:
+        let topo_json_str = "{\"arcs\":[[[0,0],[1,0]],[[1,0],[1,1]],[[1,1],[0,1],[0,0]],[[5,5],[6,6]]],\"objects\":{\"land\":{\"arcs\":[[0,1,2]],\"type\":\"Polygon\"},\"maritime\":{\"arcs\":[-2],\"type\":\"LineString\"}},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let histogram = topo.arc_reuse_histogram();
+        assert_eq!(
+            histogram.into_iter().collect::<Vec<_>>(),
+            vec![(0, 1), (1, 2), (2, 1)]
+        );
+    }
+
+    #[test]
+    fn to_string_sorted_objects() {
+        let line = Geometry::new(Value::LineString(vec![0]));
+        let topo = Topology {
+            arcs: vec![vec![vec![0., 0.], vec![1., 1.]]],
+            objects: ["roads", "borders", "coast"]
+                .iter()
+                .map(|name| NamedGeometry {
+                    name: name.to_string(),
+                    geometry: line.clone(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let expected = "{\"arcs\":[[[0.0,0.0],[1.0,1.0]]],\"objects\":{\"borders\":{\"arcs\":[0],\"type\":\"LineString\"},\"coast\":{\"arcs\":[0],\"type\":\"LineString\"},\"roads\":{\"arcs\":[0],\"type\":\"LineString\"}},\"type\":\"Topology\"}";
+        assert_eq!(topo.to_string_sorted_objects(), expected);
+    }
+
+    #[test]
+    fn feature_count() {
+        let topo_json_str = "{\"arcs\":[[[0,0],[1,1]]],\"objects\":{\"collection\":{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"},{\"coordinates\":[0,0],\"type\":\"Point\"},{\"geometries\":[{\"coordinates\":[1,1],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}],\"type\":\"GeometryCollection\"},\"empty\":{\"geometries\":[],\"type\":\"GeometryCollection\"},\"line\":{\"arcs\":[0],\"type\":\"LineString\"}},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        assert_eq!(topo.feature_count("collection"), Ok(3));
+        assert_eq!(topo.feature_count("empty"), Ok(0));
+        assert_eq!(topo.feature_count("line"), Ok(1));
+        assert_eq!(
+            topo.feature_count("foo"),
+            Err(Error::TopoToGeoUnknownKey("foo".to_string()))
+        );
+        assert_eq!(topo.total_feature_count(), 4);
+        for name in topo.list_names() {
+            assert_eq!(
+                topo.feature_count(&name).unwrap(),
+                to_geojson(&topo, &name).unwrap().features.len()
+            );
+        }
+    }
+
+    #[test]
+    fn display_topology() {
+        let topo_json_str = "{\"arcs\":[[[2.2,2.2],[3.3,3.3]]],\"objects\":{\"example\":{\"arcs\":[0],\"type\":\"LineString\"}},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let displayed = format!("{}", topo);
+        assert_eq!(displayed, topo_json_str);
+        assert_eq!(decode(displayed).try_unwrap_topology().unwrap(), topo);
+    }
+
+    #[test]
+    fn decode_lenient_transform_object_form() {
+        let topo_json_str = "{\"arcs\":[],\"objects\":{},\"transform\":{\"scale\":{\"x\":0.5,\"y\":0.25},\"translate\":{\"x\":10,\"y\":20}},\"type\":\"Topology\"}";
+        assert_eq!(
+            topo_json_str.parse::<TopoJson>(),
+            Err(Error::ScaleExpectedArray)
+        );
+
+        let options = ParseOptions {
+            lenient_transform: true,
+            ..Default::default()
+        };
+        let topo = TopoJson::from_str_with_options(topo_json_str, &options)
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(
+            topo.transform,
+            Some(TransformParams {
+                scale: [0.5, 0.25],
+                translate: [10., 20.],
+            })
+        );
+
+        // The standard form is still accepted:
+        let topo_json_str = "{\"arcs\":[],\"objects\":{},\"transform\":{\"scale\":[0.5,0.25],\"translate\":[10,20]},\"type\":\"Topology\"}";
+        assert_eq!(
+            TopoJson::from_str_with_options(topo_json_str, &options),
+            topo_json_str.parse::<TopoJson>()
+        );
+    }
+
+    #[test]
+    fn max_collection_depth() {
+        let topo_json_str = "{\"arcs\":[],\"objects\":{\"nested\":{\"geometries\":[{\"coordinates\":[0,0],\"type\":\"Point\"},{\"geometries\":[{\"geometries\":[{\"coordinates\":[1,1],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}],\"type\":\"GeometryCollection\"}],\"type\":\"GeometryCollection\"},\"point\":{\"coordinates\":[0,0],\"type\":\"Point\"}},\"type\":\"Topology\"}";
+        let mut topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(topo.max_collection_depth(), 3);
+
+        topo.objects.retain(|ng| ng.name == "point");
+        assert_eq!(topo.max_collection_depth(), 0);
+        assert_eq!(Topology::default().max_collection_depth(), 0);
+    }
+
+    #[test]
+    fn geometry_types() {
+        // This is the example from https://github.com/topojson/topojson-specification#11-examples
+        let topo_json_str = "{\"arcs\":[[[102,0],[103,1],[104,0],[105,1]],[[100,0],[101,0],[101,1],[100,1],[100,0]]],\"objects\":{\"example\":{\"geometries\":[{\"coordinates\":[102,0.5],\"type\":\"Point\"},{\"arcs\":[0],\"type\":\"LineString\"},{\"arcs\":[[1]],\"type\":\"Polygon\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let expected: Vec<String> = ["GeometryCollection", "LineString", "Point", "Polygon"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        assert_eq!(
+            topo.geometry_types().into_iter().collect::<Vec<_>>(),
+            expected
+        );
+        assert!(Topology::default().geometry_types().is_empty());
+    }
+
+    #[test]
+    fn sort_objects() {
+        let names = ["roads", "borders", "water", "coast"];
+        let mut topo = Topology {
+            arcs: vec![vec![vec![0., 0.], vec![1., 1.]]],
+            objects: names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| NamedGeometry {
+                    name: name.to_string(),
+                    geometry: Geometry::new(Value::LineString(vec![i as i32 % 2])),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let before = topo.clone();
+
+        topo.sort_objects();
+        assert_eq!(
+            topo.list_names(),
+            vec!["borders", "coast", "roads", "water"]
+        );
+        for name in names {
+            let find = |t: &Topology| {
+                t.objects
+                    .iter()
+                    .find(|ng| ng.name == name)
+                    .unwrap()
+                    .geometry
+                    .clone()
+            };
+            assert_eq!(find(&topo), find(&before));
+        }
+    }
+
+    #[test]
+    fn set_object() {
+        let line = Geometry::new(Value::LineString(vec![0]));
+        let point = Geometry::new(Value::Point(vec![0., 0.]));
+        let mut topo = Topology {
+            arcs: vec![vec![vec![0., 0.], vec![1., 1.]]],
+            objects: vec![
+                NamedGeometry {
+                    name: "a".to_string(),
+                    geometry: line.clone(),
+                },
+                NamedGeometry {
+                    name: "a".to_string(),
+                    geometry: line.clone(),
+                },
+            ],
+            ..Default::default()
+        };
+
+        // Replace the first object named "a":
+        assert_eq!(topo.set_object("a", point.clone()), Some(line.clone()));
+        assert_eq!(topo.objects[0].geometry, point);
+        assert_eq!(topo.objects[1].geometry, line);
+
+        // Insert a new object:
+        assert_eq!(topo.set_object("b", point.clone()), None);
+        assert_eq!(topo.list_names(), vec!["a", "a", "b"]);
+        assert_eq!(topo.objects[2].geometry, point);
+    }
+
+    #[test]
+    fn raw_and_decoded_arcs() {
+        let topo_json_str = "{\"arcs\":[[[4000,0],[1999,9999],[2000,-9999],[2000,9999]]],\"objects\":{},\"transform\":{\"scale\":[0.0005,0.0001],\"translate\":[100,0]},\"type\":\"Topology\"}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let raw = topo.raw_arc(0).unwrap();
+        assert_eq!(raw[1], vec![1999., 9999.]);
+        let decoded = topo.arc_coordinates(0).unwrap();
+        assert_ne!(&decoded, raw);
+        assert_eq!(decoded[1], vec![102.9995, 0.9999]);
+        assert_eq!(topo.raw_arc(1), None);
+        assert_eq!(topo.arc_coordinates(1), None);
+    }
+
+    #[test]
+    fn integer_and_float_arcs() {
+        let int_str =
+            "{\"arcs\":[[[4000,0],[1999,9999],[2000,-9999]]],\"objects\":{},\"type\":\"Topology\"}";
+        let float_str = "{\"arcs\":[[[4000.0,0.0],[1999.0,9999.0],[2000.0,-9999.0]]],\"objects\":{},\"type\":\"Topology\"}";
+        let from_int = decode(int_str.to_string()).try_unwrap_topology().unwrap();
+        let from_float = decode(float_str.to_string()).try_unwrap_topology().unwrap();
+        assert_eq!(from_int.arcs, from_float.arcs);
+        assert!(from_int.arcs_are_integral());
+        assert!(from_float.arcs_are_integral());
+
+        let mut topo = from_int;
+        topo.arcs[0][1][0] = 1999.5;
+        assert!(!topo.arcs_are_integral());
+    }
+
+    #[test]
+    fn encode_decode_topology_foreign_members() {
+        let topo_json_str = "{\"arcs\":[],\"bbox\":[0.0,0.0,1.0,1.0],\"generator\":\"tool 1.2\",\"objects\":{\"example\":{\"coordinates\":[0.5,0.5],\"type\":\"Point\",\"vendor\":1}},\"transform\":{\"scale\":[1.0,1.0],\"translate\":[0.0,0.0]},\"type\":\"Topology\",\"x-layers\":[\"a\",\"b\"],\"x-meta\":{\"type\":\"Meta\"}}";
+        let topo = decode(topo_json_str.to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        // Only the unrecognized top-level members are foreign members:
+        let keys: Vec<&String> = topo.foreign_members.as_ref().unwrap().keys().collect();
+        assert_eq!(keys, vec!["generator", "x-layers", "x-meta"]);
+        assert_eq!(topo.foreign_member("generator"), Some(&json!("tool 1.2")));
+        assert_eq!(topo.foreign_member("transform"), None);
+        assert_eq!(
+            topo.objects[0].geometry.foreign_members,
+            Some(json!({"vendor": 1}).as_object().unwrap().clone())
+        );
+
+        assert_eq!(serde_json::to_string(&topo).unwrap(), topo_json_str);
+    }
+
+    #[test]
+    fn encode_topology_with_precision() {
+        let topo = decode("{\"arcs\":[[[102.123456,0.987654],[103.5,1.0004]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"},{\"coordinates\":[102.456789,0.5],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}".to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        let rounded = topo.to_string_with_precision(2).unwrap();
+        assert_eq!(
+            rounded,
+            "{\"arcs\":[[[102.12,0.99],[103.5,1.0]]],\"objects\":{\"example\":{\"geometries\":[{\"arcs\":[0],\"type\":\"LineString\"},{\"coordinates\":[102.46,0.5],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}},\"type\":\"Topology\"}"
+        );
+        assert!(rounded.len() < serde_json::to_string(&topo).unwrap().len());
+        assert_eq!(
+            topo.to_string_with_precision(16),
+            Err(Error::InvalidPrecision(16))
+        );
+    }
+
+    #[test]
+    fn convex_hull_of_object() {
+        let topo = decode("{\"arcs\":[[[0,0],[4,2]]],\"objects\":{\"cloud\":{\"geometries\":[{\"coordinates\":[[0,4],[1,1],[2,3],[4,4]],\"type\":\"MultiPoint\"},{\"arcs\":[0],\"type\":\"LineString\"}],\"type\":\"GeometryCollection\"},\"single\":{\"coordinates\":[1,2],\"type\":\"Point\"}},\"transform\":{\"scale\":[1,1],\"translate\":[0,0]},\"type\":\"Topology\"}".to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        // The arc is delta-encoded: it goes from [0, 0] to [4, 2].
+        assert_eq!(
+            topo.convex_hull("cloud").unwrap(),
+            geojson::Value::Polygon(vec![vec![
+                vec![0., 0.],
+                vec![4., 2.],
+                vec![4., 4.],
+                vec![0., 4.],
+                vec![0., 0.],
+            ]])
+        );
+        assert_eq!(
+            topo.convex_hull("single").unwrap(),
+            geojson::Value::Point(vec![1., 2.])
+        );
+        assert_eq!(
+            topo.convex_hull("missing").err(),
+            Some(Error::TopoToGeoUnknownKey(String::from("missing")))
+        );
+    }
+
+    #[test]
+    fn rename_object() {
+        let mut topo = decode("{\"arcs\":[],\"objects\":{\"a\":{\"coordinates\":[0,0],\"type\":\"Point\"},\"b\":{\"coordinates\":[1,1],\"type\":\"Point\"}},\"type\":\"Topology\"}".to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        topo.rename_object("a", "c").unwrap();
+        assert_eq!(topo.list_names(), vec!["c", "b"]);
+        // Renaming an object to its own name is a no-op:
+        topo.rename_object("b", "b").unwrap();
+        assert_eq!(topo.list_names(), vec!["c", "b"]);
+    }
+
+    #[test]
+    fn rename_object_errors() {
+        let mut topo = decode("{\"arcs\":[],\"objects\":{\"a\":{\"coordinates\":[0,0],\"type\":\"Point\"},\"b\":{\"coordinates\":[1,1],\"type\":\"Point\"}},\"type\":\"Topology\"}".to_string())
+            .try_unwrap_topology()
+            .unwrap();
+
+        assert_eq!(
+            topo.rename_object("z", "c"),
+            Err(Error::TopoToGeoUnknownKey(String::from("z")))
+        );
+        assert_eq!(
+            topo.rename_object("a", "b"),
+            Err(Error::DuplicateObjectName(String::from("b")))
+        );
+        assert_eq!(topo.list_names(), vec!["a", "b"]);
+    }
+
+    /// A small xorshift generator, so that the random topologies below are
+    /// reproducible without depending on an external crate.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        /// A coordinate which is exactly represented in JSON (a multiple of 1/8).
+        fn coordinate(&mut self) -> f64 {
+            (self.below(4001) as f64 - 2000.) / 8.
+        }
+    }
+
+    fn random_geometry(rng: &mut Rng, arc_count: usize, depth: usize) -> Geometry {
+        let position = |rng: &mut Rng| vec![rng.coordinate(), rng.coordinate()];
+        let arc_indexes = |rng: &mut Rng| -> Vec<i32> {
+            (0..1 + rng.below(3))
+                .map(|_| {
+                    let ix = rng.below(arc_count) as i32;
+                    if rng.below(2) == 0 {
+                        ix
+                    } else {
+                        !ix
+                    }
+                })
+                .collect()
+        };
+        let kinds = match (arc_count, depth) {
+            (0, 0) => 2,
+            (0, _) => 3,
+            (_, 0) => 6,
+            _ => 7,
+        };
+        let value = match rng.below(kinds) {
+            0 => Value::Point(position(rng)),
+            1 => Value::MultiPoint((0..rng.below(4)).map(|_| position(rng)).collect()),
+            2 if arc_count == 0 => Value::GeometryCollection(
+                (0..rng.below(3))
+                    .map(|_| random_geometry(rng, arc_count, depth - 1))
+                    .collect(),
+            ),
+            2 => Value::LineString(arc_indexes(rng)),
+            3 => Value::MultiLineString((0..rng.below(3)).map(|_| arc_indexes(rng)).collect()),
+            4 => Value::Polygon((0..rng.below(3)).map(|_| arc_indexes(rng)).collect()),
+            5 => Value::MultiPolygon(
+                (0..rng.below(3))
+                    .map(|_| (0..1 + rng.below(2)).map(|_| arc_indexes(rng)).collect())
+                    .collect(),
+            ),
+            _ => Value::GeometryCollection(
+                (0..rng.below(3))
+                    .map(|_| random_geometry(rng, arc_count, depth - 1))
+                    .collect(),
+            ),
+        };
+        let mut geometry = Geometry::new(value);
+        if rng.below(2) == 0 {
+            geometry.id = Some(json!(rng.below(100)));
+        }
+        if rng.below(2) == 0 {
+            let mut properties = JsonObject::new();
+            properties.insert(String::from("name"), json!(format!("n{}", rng.below(10))));
+            geometry.properties = Some(properties);
+        }
+        geometry
+    }
+
+    fn random_topology(rng: &mut Rng) -> Topology {
+        let arcs: Vec<Arc> = (0..rng.below(5))
+            .map(|_| {
+                (0..2 + rng.below(4))
+                    .map(|_| vec![rng.coordinate(), rng.coordinate()])
+                    .collect()
+            })
+            .collect();
+        let objects = (0..rng.below(4))
+            .map(|i| NamedGeometry {
+                name: format!("object {}", i),
+                geometry: random_geometry(rng, arcs.len(), 2),
+            })
+            .collect();
+        let transform = match rng.below(2) {
+            0 => None,
+            _ => Some(TransformParams {
+                scale: [
+                    (1 + rng.below(64)) as f64 / 16.,
+                    (1 + rng.below(64)) as f64 / 16.,
+                ],
+                translate: [rng.coordinate(), rng.coordinate()],
+            }),
+        };
+        Topology {
+            arcs,
+            objects,
+            transform,
+            ..Default::default()
+        }
+    }
+
+    /// Call every public method of `topo` (the mutating ones on copies), and the
+    /// conversions of each of its objects, discarding the results: only panics matter.
+    fn exercise(topo: &Topology) {
+        let names = topo.list_names();
+        let name = names.first().map_or("missing", String::as_str);
+        let other = names.last().map_or("missing", String::as_str);
+        let bbox = [-100., -100., 100., 100.];
+
+        let _ = topo.validate();
+        let _ = topo.conformance_check();
+        let _ = encode(topo);
+        for order in [MemberOrder::Canonical, MemberOrder::Mapshaper] {
+            let _ = topo.to_string_with_member_order(order);
+        }
+        let _ = topo.to_string_sorted_objects();
+        let _ = topo.to_string_with_precision(3);
+        let _ = (topo.foreign_member("other"), topo.crs());
+        for index in 0..topo.arc_count() + 1 {
+            let _ = topo.raw_arc(index);
+            let _ = topo.arc_coordinates(index);
+            let _ = topo.objects_referencing_arc(index);
+        }
+        let _ = (topo.arcs_are_integral(), topo.object_count());
+        let _ = (topo.is_empty_objects(), topo.is_empty());
+        let _ = (topo.total_feature_count(), topo.max_collection_depth());
+        let _ = (topo.geometry_types(), topo.arc_reuse_histogram());
+        let _ = topo.split_by_object();
+        let _ = topo.shared_arcs(name, other);
+        let _ = (topo.approx_eq(topo, 1e-9), topo.same_geometry(topo, 1e-9));
+        let _ = (topo.positions_iter().count(), topo.arc_bboxes());
+        let _ = topo.bounding_box();
+        let _ = Topology::encode_arcs(topo.arcs.clone(), 1000);
+        let _ = Topology::encode_arcs(topo.arcs.clone(), 1);
+        for key in names.iter().map(String::as_str).chain(["missing"]) {
+            let _ = topo.feature_count(key);
+            let _ = topo.convex_hull(key);
+            let _ = topo.polygon_metrics(key);
+            let _ = to_geojson(topo, key);
+            let _ = to_feature(topo, key);
+            let _ = crate::to_geojson_lenient(topo, key);
+            let _ = crate::to_geojson_in_bbox(topo, key, bbox);
+            let _ = crate::to_geojson_clipped(topo, key, bbox);
+            let _ = crate::to_geojson_filtered(topo, key, |_| true);
+            let _ = crate::to_geojson_with_reproject(topo, key, |pos| pos.reverse());
+            let options = crate::ToGeoJsonOptions {
+                recompute_feature_bbox: true,
+                close_rings: true,
+                spatial_sort: true,
+                omit_empty_geometries: true,
+                ..Default::default()
+            };
+            let _ = crate::to_geojson_with_options(topo, key, &options);
+            #[cfg(feature = "wkt")]
+            let _ = crate::to_wkt(topo, key);
+            let _ = crate::write_geojson(topo, key, std::io::sink());
+            #[cfg(feature = "parallel")]
+            let _ = crate::to_geojson_parallel(topo, key);
+        }
+        for named_geom in topo.objects.iter() {
+            let _ = named_geom.geometry.bbox_of(&topo.arcs, topo.transform);
+        }
+        let _ = to_geojson_all(topo);
+        let _ = crate::to_geojson_keys(topo, &[name, other]);
+        let _ = crate::to_geojson_by_type(topo, &["Polygon", "Point"]);
+
+        let mutations: [&dyn Fn(&mut Topology); 15] = [
+            &|t| t.sort_objects(),
+            &|t| {
+                t.push_arc(vec![vec![0., 0.], vec![1., 1.]]);
+            },
+            &|t| {
+                let _ = t.push_object(
+                    String::from("new"),
+                    Geometry::new(Value::LineString(vec![0])),
+                );
+            },
+            &|t| {
+                let _ = t.rename_object(name, "renamed");
+            },
+            &|t| {
+                let geom = Geometry::new(Value::MultiPoint(vec![vec![0.], vec![]]));
+                let _ = t.set_object(other, geom);
+            },
+            &|t| {
+                let _ = t.prune_unused_arcs();
+            },
+            &|t| {
+                let _ = t.dedup_arcs_remap();
+                let _ = t.prune_unused_arcs_remap();
+            },
+            &|t| t.remove_repeated_points(),
+            &|t| {
+                let _ = t.materialize_reversed_arcs();
+            },
+            &|t| t.promote_id_from_property("name"),
+            &|t| t.map_properties(|p| p.clear()),
+            &|t| t.dequantize(),
+            &|t| {
+                t.remove_identity_transform();
+            },
+            &|t| {
+                let _ = t.transform_to(&TransformParams {
+                    scale: [0.5, 0.25],
+                    translate: [1., 2.],
+                });
+                t.scale_to_fit(800., 600., true);
+            },
+            &|t| {
+                let _ = t.requantize(1000);
+            },
+        ];
+        for mutate in mutations {
+            let mut topo = topo.clone();
+            mutate(&mut topo);
+            let _ = topo.validate();
+        }
+    }
+
+    #[test]
+    fn random_topologies_round_trip() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..500 {
+            let topo = random_topology(&mut rng);
+            let topo_json_str = encode(&topo);
+            let parsed = decode(topo_json_str.clone()).try_unwrap_topology().unwrap();
+            assert_eq!(parsed, topo, "{}", topo_json_str);
+            assert_eq!(parsed.validate(), Ok(()));
+            assert!(to_geojson_all(&parsed).is_ok(), "{}", topo_json_str);
+        }
+    }
+
+    #[test]
+    fn corrupted_topologies_dont_panic() {
+        // Each serialized topology is corrupted by a few random substitutions: parsing
+        // may fail, but neither parsing nor converting the result may panic.
+        let replacements = [
+            "[", "]", "{", "}", ",", "0", "-1", "[1]", "\"arcs\"", "null",
+        ];
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..2000 {
+            let mut topo_json_str = encode(&random_topology(&mut rng));
+            for _ in 0..1 + rng.below(3) {
+                let mut at = rng.below(topo_json_str.len());
+                while !topo_json_str.is_char_boundary(at) {
+                    at -= 1;
+                }
+                let len = topo_json_str[at..].chars().next().map_or(0, char::len_utf8);
+                let replacement = replacements[rng.below(replacements.len())];
+                topo_json_str.replace_range(at..at + len, replacement);
+            }
+            if let Ok(TopoJson::Topology(topo)) = topo_json_str.parse::<TopoJson>() {
+                exercise(&topo);
+            }
+        }
+
+        // Inputs which used to panic:
+        let transform = "\"transform\":{\"scale\":[1,1],\"translate\":[0,0]}";
+        assert_eq!(
+            "{\"arcs\":[],\"objects\":{},\"transform\":{\"scale\":[1],\"translate\":[0,0]},\"type\":\"Topology\"}"
+                .parse::<TopoJson>()
+                .err(),
+            Some(Error::TransformExpectedTwoValues)
+        );
+        for objects in [
+            "\"arcs\":[[[0],[1,1]]],\"objects\":{\"a\":{\"arcs\":[0],\"type\":\"LineString\"}}",
+            "\"arcs\":[[[0,0],[1]]],\"objects\":{\"a\":{\"arcs\":[0],\"type\":\"LineString\"}}",
+            "\"arcs\":[[[0,0],[1],[2,2],[0,0]]],\"objects\":{\"a\":{\"arcs\":[[0]],\"type\":\"Polygon\"}}",
+            "\"arcs\":[],\"objects\":{\"a\":{\"coordinates\":[1],\"type\":\"Point\"}}",
+            "\"arcs\":[],\"objects\":{\"a\":{\"coordinates\":[[0,0],[1]],\"type\":\"MultiPoint\"}}",
+            "\"arcs\":[[[0,0],[1]]],\"objects\":{\"a\":{\"geometries\":[{\"coordinates\":[[0,0],[1]],\"type\":\"MultiPoint\"},{\"arcs\":[0],\"type\":\"LineString\"},{\"coordinates\":[1],\"type\":\"Point\"}],\"type\":\"GeometryCollection\"}}",
+            "\"arcs\":[],\"objects\":{\"a\":{\"arcs\":[-4],\"type\":\"LineString\"}}",
+        ] {
+            // With and without transform (the positions are delta-encoded with one):
+            for transform in ["", transform] {
+                let topo_json_str = format!("{{{},{},\"type\":\"Topology\"}}", objects, transform)
+                    .replace(",,", ",");
+                match topo_json_str.parse::<TopoJson>() {
+                    Ok(TopoJson::Topology(topo)) => exercise(&topo),
+                    other => panic!("{}: {:?}", topo_json_str, other),
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,167 @@
+// Copyright 2018 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::marker::PhantomData;
+
+use crate::{Error, Topology, TransformParams};
+
+/// Marker of a [`TypedTopology`] whose arcs and point coordinates are quantized
+/// (it always has a transform).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quantized {}
+
+/// Marker of a [`TypedTopology`] whose arcs and point coordinates are real-world
+/// coordinates (it never has a transform).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Geographic {}
+
+/// A Topology whose coordinate space ([`Quantized`] or [`Geographic`]) is
+/// part of its type.
+///
+/// The topology can't be modified once wrapped, so its coordinates stay in the
+/// space given by `S`. Use [`TypedTopology::into_inner`] to modify it, and wrap it again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedTopology<S> {
+    topology: Topology,
+    space: PhantomData<S>,
+}
+
+/// A Topology with quantized coordinates.
+pub type QuantizedTopology = TypedTopology<Quantized>;
+
+/// A Topology with real-world coordinates.
+pub type GeographicTopology = TypedTopology<Geographic>;
+
+impl<S> TypedTopology<S> {
+    fn wrap(topology: Topology) -> Self {
+        TypedTopology {
+            topology,
+            space: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped topology.
+    pub fn topology(&self) -> &Topology {
+        &self.topology
+    }
+
+    /// Unwrap the topology.
+    pub fn into_inner(self) -> Topology {
+        self.topology
+    }
+}
+
+impl TypedTopology<Quantized> {
+    /// Wrap a topology having a transform.
+    pub fn new(topology: Topology) -> Result<Self, Error> {
+        match topology.transform {
+            Some(..) => Ok(Self::wrap(topology)),
+            None => Err(Error::TopologyExpectedTransform),
+        }
+    }
+
+    /// Returns the transform of the topology.
+    pub fn transform(&self) -> TransformParams {
+        self.topology.transform.unwrap()
+    }
+
+    /// Convert the topology to real-world coordinates (see [`Topology::dequantize`]).
+    pub fn dequantize(self) -> GeographicTopology {
+        GeographicTopology::new(self.topology)
+    }
+}
+
+impl TypedTopology<Geographic> {
+    /// Wrap a topology, dequantizing it if it has a transform.
+    pub fn new(mut topology: Topology) -> Self {
+        topology.dequantize();
+        Self::wrap(topology)
+    }
+
+    /// Quantize the topology to a grid of `quantization` × `quantization` positions
+    /// (see [`Topology::requantize`]).
+    pub fn quantize(self, quantization: u32) -> Result<QuantizedTopology, Error> {
+        let mut topology = self.topology;
+        topology.requantize(quantization)?;
+        Ok(QuantizedTopology::wrap(topology))
+    }
+}
+
+impl TryFrom<Topology> for QuantizedTopology {
+    type Error = Error;
+
+    fn try_from(topology: Topology) -> Result<Self, Error> {
+        QuantizedTopology::new(topology)
+    }
+}
+
+impl From<Topology> for GeographicTopology {
+    fn from(topology: Topology) -> Self {
+        GeographicTopology::new(topology)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GeographicTopology, QuantizedTopology};
+    use crate::{Error, TopoJson, Topology};
+
+    fn decode(s: &str) -> Topology {
+        s.parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap()
+    }
+
+    #[test]
+    fn typed_topology_transitions() {
+        let topo = decode("{\"arcs\":[[[0,0],[2,0],[0,2],[-2,0],[0,-2]]],\"objects\":{\"example\":{\"arcs\":[[0]],\"type\":\"Polygon\"}},\"transform\":{\"scale\":[0.5,0.5],\"translate\":[10,20]},\"type\":\"Topology\"}");
+
+        let quantized = QuantizedTopology::try_from(topo.clone()).unwrap();
+        assert_eq!(quantized.transform(), topo.transform.unwrap());
+
+        let geographic = quantized.dequantize();
+        assert!(geographic.topology().transform.is_none());
+        assert_eq!(
+            geographic.topology().arcs[0],
+            vec![
+                vec![10., 20.],
+                vec![11., 20.],
+                vec![11., 21.],
+                vec![10., 21.],
+                vec![10., 20.]
+            ]
+        );
+        assert_eq!(GeographicTopology::from(topo.clone()), geographic);
+
+        let requantized = geographic.quantize(3).unwrap();
+        assert!(requantized.topology().same_geometry(&topo, 1e-9));
+        assert_eq!(
+            GeographicTopology::from(topo).quantize(1).err(),
+            Some(Error::InvalidQuantization(1))
+        );
+    }
+
+    #[test]
+    fn quantized_topology_requires_transform() {
+        let topo =
+            decode("{\"arcs\":[[[0.5,0.5],[1.5,1.5]]],\"objects\":{},\"type\":\"Topology\"}");
+        assert_eq!(
+            QuantizedTopology::new(topo.clone()).err(),
+            Some(Error::TopologyExpectedTransform)
+        );
+        // A topology without transform is already geographic:
+        assert_eq!(GeographicTopology::new(topo.clone()).into_inner(), topo);
+    }
+}
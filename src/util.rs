@@ -12,11 +12,173 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::json::{JsonObject, JsonValue};
-use crate::{Arc, ArcIndexes, Bbox, Error, Geometry, NamedGeometry, Position, TransformParams};
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::{MapAccess, SeqAccess, Visitor};
+
+use crate::json::{Deserialize, Deserializer, JsonObject, JsonValue};
+use crate::{
+    member_names, Arc, ArcIndexes, ArcRef, Bbox, Error, Geometry, NamedGeometry, ParseOptions,
+    Position, TopoJson, Topology, TransformParams,
+};
+
+/// A JSON object along with the order of the keys of its 'objects' member (if it is
+/// an object), which a `JsonObject` doesn't keep unless the `preserve_order` feature
+/// of serde_json is enabled.
+///
+/// Used to parse the objects of a topology in the order of the input.
+pub struct OrderedObject {
+    pub object: JsonObject,
+    pub objects_order: Vec<String>,
+}
+
+impl OrderedObject {
+    /// Parse the object, ordering the objects of a topology like its 'objects' member.
+    pub fn into_topojson(self, options: &ParseOptions) -> Result<TopoJson, Error> {
+        let mut topo = TopoJson::from_json_object_with_options(self.object, options)?;
+        if let TopoJson::Topology(ref mut t) = topo {
+            restore_objects_order(&mut t.objects, &self.objects_order);
+        }
+        Ok(topo)
+    }
+}
+
+/// Order `objects` by the position of their name in `order`.
+pub fn restore_objects_order(objects: &mut [NamedGeometry], order: &[String]) {
+    let positions: HashMap<&str, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect();
+    objects.sort_by_key(|ng| positions.get(ng.name.as_str()).copied());
+}
+
+impl<'de> Deserialize<'de> for OrderedObject {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedObjectVisitor;
+
+        impl<'de> Visitor<'de> for OrderedObjectVisitor {
+            type Value = OrderedObject;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OrderedObject, A::Error> {
+                let mut object = JsonObject::new();
+                let mut objects_order = Vec::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    let value = if key == member_names::OBJECTS {
+                        let member: OrderedMember = map.next_value()?;
+                        objects_order = member.keys;
+                        member.value
+                    } else {
+                        map.next_value()?
+                    };
+                    object.insert(key, value);
+                }
+                Ok(OrderedObject {
+                    object,
+                    objects_order,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(OrderedObjectVisitor)
+    }
+}
+
+/// Any JSON value, with its keys in input order when it is an object.
+pub struct OrderedMember {
+    pub value: JsonValue,
+    pub keys: Vec<String>,
+}
+
+impl From<JsonValue> for OrderedMember {
+    fn from(value: JsonValue) -> Self {
+        OrderedMember {
+            value,
+            keys: vec![],
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OrderedMember {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedMemberVisitor;
+
+        impl<'de> Visitor<'de> for OrderedMemberVisitor {
+            type Value = OrderedMember;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("any JSON value")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<OrderedMember, E> {
+                Ok(JsonValue::from(v).into())
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<OrderedMember, E> {
+                Ok(JsonValue::from(v).into())
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<OrderedMember, E> {
+                Ok(JsonValue::from(v).into())
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<OrderedMember, E> {
+                Ok(JsonValue::from(v).into())
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<OrderedMember, E> {
+                Ok(JsonValue::from(v).into())
+            }
+
+            fn visit_unit<E>(self) -> Result<OrderedMember, E> {
+                Ok(JsonValue::Null.into())
+            }
+
+            fn visit_none<E>(self) -> Result<OrderedMember, E> {
+                Ok(JsonValue::Null.into())
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<OrderedMember, A::Error> {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(JsonValue::Array(values).into())
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OrderedMember, A::Error> {
+                let mut object = JsonObject::new();
+                let mut keys = Vec::new();
+                while let Some((key, value)) = map.next_entry::<String, JsonValue>()? {
+                    keys.push(key.clone());
+                    object.insert(key, value);
+                }
+                Ok(OrderedMember {
+                    value: JsonValue::Object(object),
+                    keys,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(OrderedMemberVisitor)
+    }
+}
+
+/// Convert an error of serde_json while reading, keeping the I/O errors.
+pub fn from_json_error(error: serde_json::Error) -> Error {
+    match error.classify() {
+        serde_json::error::Category::Io => Error::from(std::io::Error::from(error)),
+        _ => Error::MalformedJson,
+    }
+}
 
 pub fn expect_type(value: &mut JsonObject) -> Result<String, Error> {
-    let prop = expect_property(value, "type")?;
+    let prop = expect_property(value, member_names::TYPE)?;
     expect_string(prop)
 }
 
@@ -27,18 +189,31 @@ fn expect_string(value: JsonValue) -> Result<String, Error> {
     }
 }
 
-pub fn expect_f64(value: &JsonValue) -> Result<f64, Error> {
-    match value.as_f64() {
-        Some(v) => Ok(v),
-        None => Err(Error::ExpectedF64Value),
+/// With [`ParseOptions::numeric_strings`], a string holding a finite number
+/// (like `"100.0"`, but not `"NaN"` or `"inf"`) is also accepted.
+pub fn expect_f64(value: &JsonValue, options: &ParseOptions) -> Result<f64, Error> {
+    match value {
+        JsonValue::String(s) if options.numeric_strings => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or(Error::ExpectedF64Value),
+        _ => value.as_f64().ok_or(Error::ExpectedF64Value),
     }
 }
 
-pub fn expect_i32(value: &JsonValue) -> Result<i32, Error> {
-    match value.as_i64() {
-        Some(v) => Ok(v as i32),
-        None => Err(Error::Expectedi32Value),
-    }
+/// With [`ParseOptions::numeric_strings`], a string holding an integer
+/// (like `"-2"`) is also accepted.
+pub fn expect_i32(value: &JsonValue, options: &ParseOptions) -> Result<i32, Error> {
+    let v = match value {
+        JsonValue::String(s) if options.numeric_strings => s
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| Error::Expectedi32Value)?,
+        _ => value.as_i64().ok_or(Error::Expectedi32Value)?,
+    };
+    i32::try_from(v).map_err(|_| Error::ArcIndexOutOfBounds(v))
 }
 
 pub fn expect_array(value: &JsonValue) -> Result<&Vec<JsonValue>, Error> {
@@ -71,19 +246,22 @@ pub fn get_foreign_members(object: JsonObject) -> Result<Option<JsonObject>, Err
 }
 
 fn get_coords_value(object: &mut JsonObject) -> Result<JsonValue, Error> {
-    expect_property(object, "coordinates")
+    expect_property(object, member_names::COORDINATES)
 }
 
 fn get_arcs_value(object: &mut JsonObject) -> Result<JsonValue, Error> {
-    expect_property(object, "arcs")
+    expect_property(object, member_names::ARCS)
 }
 
 /// Retrieve the 'arcs' member of a Topology.
 ///
 /// Used by Topology.
-pub fn get_arcs_position(object: &mut JsonObject) -> Result<Vec<Arc>, Error> {
-    match object.remove("arcs") {
-        Some(a) => json_to_arc_positions(&a),
+pub fn get_arcs_position(
+    object: &mut JsonObject,
+    options: &ParseOptions,
+) -> Result<Vec<Arc>, Error> {
+    match object.remove(member_names::ARCS) {
+        Some(a) => json_to_arc_positions(&a, options),
         None => Err(Error::TopologyExpectedArcs),
     }
 }
@@ -91,55 +269,87 @@ pub fn get_arcs_position(object: &mut JsonObject) -> Result<Vec<Arc>, Error> {
 /// Retrieve a single Position from the value of the "coordinates" key.
 ///
 /// Used by Value::Point
-pub fn get_coords_one_pos(object: &mut JsonObject) -> Result<Position, Error> {
+pub fn get_coords_one_pos(
+    object: &mut JsonObject,
+    options: &ParseOptions,
+) -> Result<Position, Error> {
     let coords_json = get_coords_value(object)?;
-    json_to_position(&coords_json)
+    json_to_position(&coords_json, options)
 }
 
 /// Retrieve a one dimensional Vec of Positions from the value of the "coordinates" key.
 ///
 /// Used by Value::MultiPoint
-pub fn get_coords_1d_pos(object: &mut JsonObject) -> Result<Vec<Position>, Error> {
+pub fn get_coords_1d_pos(
+    object: &mut JsonObject,
+    options: &ParseOptions,
+) -> Result<Vec<Position>, Error> {
     let coords_json = get_coords_value(object)?;
-    json_to_1d_positions(&coords_json)
+    json_to_1d_positions(&coords_json, options)
 }
 
 /// Retrieve an ArcIndexes from the value of the "arcs" member of a Geometry.
 ///
 /// Used by Value::LineString
-pub fn get_arc_ix(object: &mut JsonObject) -> Result<ArcIndexes, Error> {
+pub fn get_arc_ix(object: &mut JsonObject, options: &ParseOptions) -> Result<ArcIndexes, Error> {
     let arc_indexes_json = get_arcs_value(object)?;
-    json_to_arc_indexes(&arc_indexes_json)
+    json_to_arc_indexes(&arc_indexes_json, options)
 }
 
 /// Retrieve a one dimensional Vec of ArcIndexes from the value
 /// of the 'arcs' member of a Geometry.
 ///
 /// Used by Value::MultiLineString and Value::Polygon
-pub fn get_arc_ix_1d(object: &mut JsonObject) -> Result<Vec<ArcIndexes>, Error> {
+pub fn get_arc_ix_1d(
+    object: &mut JsonObject,
+    options: &ParseOptions,
+) -> Result<Vec<ArcIndexes>, Error> {
     let arc_indexes_json = get_arcs_value(object)?;
-    json_to_1d_arc_indexes(&arc_indexes_json)
+    json_to_1d_arc_indexes(&arc_indexes_json, options)
 }
 
 /// Retrieve a two dimensional Vec of ArcIndexes from the value
 /// of the 'arcs' member of a Geometry.
 ///
 /// Used by Value::MultiPolygon
-pub fn get_arc_ix_2d(object: &mut JsonObject) -> Result<Vec<Vec<ArcIndexes>>, Error> {
+pub fn get_arc_ix_2d(
+    object: &mut JsonObject,
+    options: &ParseOptions,
+) -> Result<Vec<Vec<ArcIndexes>>, Error> {
     let arc_indexes_json = get_arcs_value(object)?;
-    json_to_2d_arc_indexes(&arc_indexes_json)
+    json_to_2d_arc_indexes(&arc_indexes_json, options)
+}
+
+/// Locate `error` within the nested object `segment` (such as `geometry 2`),
+/// prefixing its path if it has already been located within a deeper object.
+pub fn at_path(error: Error, segment: String) -> Error {
+    match error {
+        Error::At { path, source } => Error::At {
+            path: format!("{}, {}", segment, path),
+            source,
+        },
+        error => Error::At {
+            path: segment,
+            source: Box::new(error),
+        },
+    }
 }
 
 /// Retrieve the geometries contained in the 'geometries' member of a GeometryCollection.
 ///
 /// Used by Value::GeometryCollection
-pub fn get_geometries(object: &mut JsonObject) -> Result<Vec<Geometry>, Error> {
-    let geometries_json = expect_property(object, "geometries")?;
+pub fn get_geometries(
+    object: &mut JsonObject,
+    options: &ParseOptions,
+) -> Result<Vec<Geometry>, Error> {
+    let geometries_json = expect_property(object, member_names::GEOMETRIES)?;
     let geometries_array = expect_owned_array(geometries_json)?;
+    check_array_len(geometries_array.len(), options.array_len_limit())?;
     let mut geometries = Vec::with_capacity(geometries_array.len());
-    for json in geometries_array {
-        let obj = expect_owned_object(json)?;
-        let geometry = Geometry::from_json_object(obj)?;
+    for (i, json) in geometries_array.into_iter().enumerate() {
+        let geometry = expect_owned_object(json)
+            .and_then(|obj| Geometry::from_json_object_with_options(obj, options))
+            .map_err(|e| at_path(e, format!("geometry {}", i)))?;
         geometries.push(geometry);
     }
     Ok(geometries)
@@ -147,12 +357,12 @@ pub fn get_geometries(object: &mut JsonObject) -> Result<Vec<Geometry>, Error> {
 
 /// Used by Geometry
 pub fn get_id(object: &mut JsonObject) -> Result<Option<JsonValue>, Error> {
-    Ok(object.remove("id"))
+    Ok(object.remove(member_names::ID))
 }
 
 /// Used by Topology and Geometry
 pub fn get_bbox(object: &mut JsonObject) -> Result<Option<Bbox>, Error> {
-    let bbox_json = match object.remove("bbox") {
+    let bbox_json = match object.remove(member_names::BBOX) {
         Some(b) => b,
         None => return Ok(None),
     };
@@ -164,43 +374,133 @@ pub fn get_bbox(object: &mut JsonObject) -> Result<Option<Bbox>, Error> {
         .into_iter()
         .map(|i| i.as_f64().ok_or(Error::BboxExpectedNumericValues))
         .collect::<Result<Vec<_>, _>>()?;
+    check_finite(&bbox, Error::BboxNotFinite)?;
     Ok(Some(bbox))
 }
 
+/// Ensure that all the `values` are finite, returning `err` otherwise.
+///
+/// JSON numbers can't be NaN or infinite, but this protects against values
+/// which can't be represented as a f64 and against non-JSON deserializers.
+pub fn check_finite(values: &[f64], err: Error) -> Result<(), Error> {
+    if values.iter().all(|v| v.is_finite()) {
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+/// Ensure that every position of the arcs and of the point geometries
+/// of `topo` has at least two components.
+///
+/// Used by Topology::validate
+pub fn validate_positions(topo: &Topology) -> Result<(), Error> {
+    let check = |pos: &Position| match pos.len() {
+        0 | 1 => Err(Error::PositionTooShort { found: pos.len() }),
+        _ => Ok(()),
+    };
+    topo.arcs.iter().flatten().try_for_each(check)?;
+    let mut result = Ok(());
+    for named_geom in topo.objects.iter() {
+        named_geom.geometry.for_each_position(&mut |pos| {
+            if result.is_ok() {
+                result = check(pos);
+            }
+        });
+    }
+    result
+}
+
+/// Check that the foreign members of the topology and of its geometries
+/// don't use the name of a member of the specification.
+pub fn validate_foreign_members(topo: &Topology) -> Result<(), Error> {
+    let find_reserved = |foreign_members: &Option<JsonObject>, reserved: &dyn Fn(&str) -> bool| {
+        foreign_members
+            .iter()
+            .flat_map(|fm| fm.keys())
+            .find(|key| reserved(key))
+            .map(|key| Error::ReservedForeignMemberKey(key.to_owned()))
+    };
+    let mut error = find_reserved(&topo.foreign_members, &|key| {
+        member_names::TOPOLOGY_MEMBERS.contains(&key)
+    });
+    for named_geom in topo.objects.iter() {
+        named_geom.geometry.for_each_geometry(&mut |g| {
+            if error.is_none() {
+                error = find_reserved(&g.foreign_members, &|key| g.is_reserved_member(key));
+            }
+        });
+    }
+    error.map_or(Ok(()), Err)
+}
+
+/// Retrieve the values of the 'scale' or 'translate' member of a transform.
+///
+/// The `{"x": .., "y": ..}` object form is only accepted with
+/// [`ParseOptions::lenient_transform`], and the numbers encoded as strings with
+/// [`ParseOptions::numeric_strings`] (non-finite values are then left to be
+/// reported as [`Error::TransformNotFinite`]).
+fn get_transform_pair(
+    value: &JsonValue,
+    options: &ParseOptions,
+    expected_array: Error,
+    expected_numeric: Error,
+) -> Result<Vec<f64>, Error> {
+    let as_f64 = |v: &JsonValue| match v {
+        JsonValue::String(s) if options.numeric_strings => s.trim().parse().ok(),
+        _ => v.as_f64(),
+    };
+    match value {
+        JsonValue::Array(a) => a
+            .iter()
+            .map(as_f64)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(expected_numeric),
+        JsonValue::Object(o) if options.lenient_transform => {
+            match (o.get("x").and_then(as_f64), o.get("y").and_then(as_f64)) {
+                (Some(x), Some(y)) => Ok(vec![x, y]),
+                _ => Err(expected_numeric),
+            }
+        }
+        _ => Err(expected_array),
+    }
+}
+
 /// Retrieve the Transforms used by the Topology if any.
 ///
 /// Used by Topology
-pub fn get_scale_translate(object: &mut JsonObject) -> Result<Option<TransformParams>, Error> {
-    match object.remove("transform") {
+pub fn get_scale_translate(
+    object: &mut JsonObject,
+    options: &ParseOptions,
+) -> Result<Option<TransformParams>, Error> {
+    match object.remove(member_names::TRANSFORM) {
         None => Ok(None),
         Some(b) => {
             let tr_json = expect_object(&b)?;
-            let scale_json = match tr_json.get("scale") {
-                Some(b) => b,
+            let scale = match tr_json.get(member_names::SCALE) {
+                Some(b) => get_transform_pair(
+                    b,
+                    options,
+                    Error::ScaleExpectedArray,
+                    Error::ScaleExpectedNumericValues,
+                )?,
                 None => return Err(Error::TransformExpectedScale),
             };
-            let scale_array = match scale_json {
-                JsonValue::Array(a) => a,
-                _ => return Err(Error::ScaleExpectedArray),
-            };
-            let scale = scale_array
-                .iter()
-                .map(|i| i.as_f64().ok_or(Error::ScaleExpectedNumericValues))
-                .collect::<Result<Vec<_>, _>>()?;
-
-            let translate_json = match tr_json.get("translate") {
-                Some(b) => b,
+            let translate = match tr_json.get(member_names::TRANSLATE) {
+                Some(b) => get_transform_pair(
+                    b,
+                    options,
+                    Error::TranslateExpectedArray,
+                    Error::TranslateExpectedNumericValues,
+                )?,
                 None => return Err(Error::TransformExpectedTranslate),
             };
-            let translate_array = match translate_json {
-                JsonValue::Array(a) => a,
-                _ => return Err(Error::TranslateExpectedArray),
-            };
-            let translate = translate_array
-                .iter()
-                .map(|i| i.as_f64().ok_or(Error::TranslateExpectedNumericValues))
-                .collect::<Result<Vec<_>, _>>()?;
 
+            if scale.len() < 2 || translate.len() < 2 {
+                return Err(Error::TransformExpectedTwoValues);
+            }
+            check_finite(&scale, Error::TransformNotFinite)?;
+            check_finite(&translate, Error::TransformNotFinite)?;
             Ok(Some(TransformParams {
                 scale: [scale[0], scale[1]],
                 translate: [translate[0], translate[1]],
@@ -213,7 +513,7 @@ pub fn get_scale_translate(object: &mut JsonObject) -> Result<Option<TransformPa
 ///
 /// Used by Geometry
 pub fn get_properties(object: &mut JsonObject) -> Result<Option<JsonObject>, Error> {
-    match object.remove("properties") {
+    match object.remove(member_names::PROPERTIES) {
         // If their is any 'properties' member, it must be an Object:
         Some(JsonValue::Object(properties)) => Ok(Some(properties)),
         // Null is handled as if their is no 'properties' member:
@@ -225,17 +525,22 @@ pub fn get_properties(object: &mut JsonObject) -> Result<Option<JsonObject>, Err
 /// Retrieve the 'objects' member of a Topololy.
 ///
 /// Used by Topology
-pub fn get_objects(object: &mut JsonObject) -> Result<Vec<NamedGeometry>, Error> {
-    match object.remove("objects") {
+pub fn get_objects(
+    object: &mut JsonObject,
+    options: &ParseOptions,
+) -> Result<Vec<NamedGeometry>, Error> {
+    match object.remove(member_names::OBJECTS) {
         // The ' member must exists and must be an Object:
         Some(JsonValue::Object(ref mut objects_json)) => {
             let keys: Vec<String> = objects_json.keys().map(|a| a.to_owned()).collect();
             let mut res = Vec::with_capacity(keys.len());
             for key in keys {
-                let g = expect_owned_object(objects_json.remove(&key).unwrap())?;
+                let geometry = expect_owned_object(objects_json.remove(&key).unwrap())
+                    .and_then(|g| Geometry::from_json_object_with_options(g, options))
+                    .map_err(|e| at_path(e, format!("object '{}'", key)))?;
                 res.push(NamedGeometry {
                     name: key,
-                    geometry: Geometry::from_json_object(g)?,
+                    geometry,
                 });
             }
             Ok(res)
@@ -244,60 +549,135 @@ pub fn get_objects(object: &mut JsonObject) -> Result<Vec<NamedGeometry>, Error>
     }
 }
 
-fn json_to_position(json: &JsonValue) -> Result<Position, Error> {
-    let coords_array = expect_array(json)?;
+/// Get the position in the 'arcs' array of the arc referenced by an arc index,
+/// following the convention of the spec for reversed arcs (`~i`).
+pub fn normalize_arc_index(ix: i32) -> usize {
+    ArcRef::from(ix).index()
+}
+
+/// Build the arc index referencing the arc at position `new_ix`, with the
+/// same direction as the arc index `ix`.
+pub fn remap_arc_index(ix: i32, new_ix: usize) -> i32 {
+    if ix < 0 {
+        !(new_ix as i32)
+    } else {
+        new_ix as i32
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+fn hash_positions<'a, I: Iterator<Item = &'a Position>>(positions: I) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for position in positions {
+        for coord in position {
+            for byte in coord.to_bits().to_le_bytes() {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        // Separate positions so that [[0, 0], [0]] and [[0], [0, 0]] differ:
+        hash ^= 0xff;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Compute a deterministic hash of the positions of an arc.
+///
+/// The key only depends on the bit patterns of the coordinates: two arcs
+/// get the same key only if they are equal bit-for-bit (no epsilon is
+/// applied, and `0.0` and `-0.0` are different). This is meant to be used
+/// on absolute coordinates: delta-encoded arcs should be decoded first.
+pub fn arc_key(arc: &Arc) -> u64 {
+    hash_positions(arc.iter())
+}
+
+/// Compute the key that [`arc_key`] would return for the reverse of `arc`.
+///
+/// A lookup with this key in a map built with [`arc_key`] finds the arcs that
+/// are the exact reverse of `arc`.
+pub fn arc_key_reversed(arc: &Arc) -> u64 {
+    hash_positions(arc.iter().rev())
+}
+
+fn json_to_position(json: &JsonValue, options: &ParseOptions) -> Result<Position, Error> {
+    let coords_array = expect_bounded_array(json, options.array_len_limit())?;
     let mut coords = Vec::with_capacity(coords_array.len());
     for position in coords_array {
-        coords.push(expect_f64(position)?);
+        coords.push(expect_f64(position, options)?);
     }
     Ok(coords)
 }
 
-fn json_to_1d_positions(json: &JsonValue) -> Result<Vec<Position>, Error> {
-    let coords_array = expect_array(json)?;
+fn json_to_1d_positions(json: &JsonValue, options: &ParseOptions) -> Result<Vec<Position>, Error> {
+    let coords_array = expect_bounded_array(json, options.array_len_limit())?;
     let mut coords = Vec::with_capacity(coords_array.len());
     for item in coords_array {
-        coords.push(json_to_position(item)?);
+        coords.push(json_to_position(item, options)?);
     }
     Ok(coords)
 }
 
-fn json_to_arc_indexes(json: &JsonValue) -> Result<ArcIndexes, Error> {
-    let arc_array = expect_array(json)?;
+fn json_to_arc_indexes(json: &JsonValue, options: &ParseOptions) -> Result<ArcIndexes, Error> {
+    let arc_array = expect_bounded_array(json, options.array_len_limit())?;
     let mut arc_ixs = Vec::with_capacity(arc_array.len());
     for item in arc_array {
-        arc_ixs.push(expect_i32(item)?);
+        arc_ixs.push(expect_i32(item, options)?);
     }
     Ok(arc_ixs)
 }
 
-fn json_to_1d_arc_indexes(json: &JsonValue) -> Result<Vec<ArcIndexes>, Error> {
-    let arc_array = expect_array(json)?;
+fn json_to_1d_arc_indexes(
+    json: &JsonValue,
+    options: &ParseOptions,
+) -> Result<Vec<ArcIndexes>, Error> {
+    let arc_array = expect_bounded_array(json, options.array_len_limit())?;
     let mut arc_ixs = Vec::with_capacity(arc_array.len());
     for item in arc_array {
-        arc_ixs.push(json_to_arc_indexes(item)?);
+        arc_ixs.push(json_to_arc_indexes(item, options)?);
     }
     Ok(arc_ixs)
 }
 
-fn json_to_2d_arc_indexes(json: &JsonValue) -> Result<Vec<Vec<ArcIndexes>>, Error> {
-    let arc_array = expect_array(json)?;
+fn json_to_2d_arc_indexes(
+    json: &JsonValue,
+    options: &ParseOptions,
+) -> Result<Vec<Vec<ArcIndexes>>, Error> {
+    let arc_array = expect_bounded_array(json, options.array_len_limit())?;
     let mut arc_ixs = Vec::with_capacity(arc_array.len());
     for item in arc_array {
-        arc_ixs.push(json_to_1d_arc_indexes(item)?);
+        arc_ixs.push(json_to_1d_arc_indexes(item, options)?);
     }
     Ok(arc_ixs)
 }
 
-fn json_to_arc_positions(json: &JsonValue) -> Result<Vec<Arc>, Error> {
-    let coords_array = expect_array(json)?;
+fn json_to_arc_positions(json: &JsonValue, options: &ParseOptions) -> Result<Vec<Arc>, Error> {
+    let coords_array = expect_bounded_array(json, options.array_len_limit())?;
     let mut arcs = Vec::with_capacity(coords_array.len());
     for item in coords_array {
-        arcs.push(json_to_1d_positions(item)?);
+        arcs.push(json_to_1d_positions(item, options)?);
     }
     Ok(arcs)
 }
 
+/// Like `expect_array`, also failing with [`Error::InputTooLarge`] if the
+/// array has more than `max_len` items (before allocating storage for them).
+fn expect_bounded_array(value: &JsonValue, max_len: usize) -> Result<&Vec<JsonValue>, Error> {
+    let array = expect_array(value)?;
+    check_array_len(array.len(), max_len)?;
+    Ok(array)
+}
+
+fn check_array_len(len: usize, max_len: usize) -> Result<(), Error> {
+    if len > max_len {
+        Err(Error::InputTooLarge { len, max_len })
+    } else {
+        Ok(())
+    }
+}
+
 fn expect_owned_array(value: JsonValue) -> Result<Vec<JsonValue>, Error> {
     match value {
         JsonValue::Array(v) => Ok(v),
@@ -311,3 +691,196 @@ fn expect_owned_object(value: JsonValue) -> Result<JsonObject, Error> {
         _ => Err(Error::ExpectedObjectValue),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        arc_key, arc_key_reversed, check_finite, validate_foreign_members, validate_positions,
+    };
+    use crate::json::JsonObject;
+    use crate::{Error, ParseOptions, TopoJson, TransformParams, Value};
+    use std::collections::HashMap;
+
+    #[test]
+    fn array_length_limit() {
+        let options = ParseOptions {
+            max_array_len: Some(3),
+            ..Default::default()
+        };
+        let topo_json_str = "{\"arcs\":[[[0,0],[1,0],[1,1]]],\"objects\":{\"line\":{\"arcs\":[0],\"type\":\"LineString\"}},\"type\":\"Topology\"}";
+        assert!(TopoJson::from_str_with_options(topo_json_str, &options).is_ok());
+
+        let topo_json_str = topo_json_str.replace("[1,1]]]", "[1,1],[0,1]]]");
+        assert_eq!(
+            TopoJson::from_str_with_options(&topo_json_str, &options).err(),
+            Some(Error::InputTooLarge { len: 4, max_len: 3 })
+        );
+        // The default limit is much larger:
+        assert!(topo_json_str.parse::<TopoJson>().is_ok());
+
+        let geometry_json_str =
+            "{\"coordinates\":[[0,0],[1,1],[2,2],[3,3]],\"type\":\"MultiPoint\"}";
+        assert_eq!(
+            TopoJson::from_str_with_options(geometry_json_str, &options).err(),
+            Some(Error::InputTooLarge { len: 4, max_len: 3 })
+        );
+    }
+
+    #[test]
+    fn numeric_strings() {
+        let topo_json_str = "{\"arcs\":[[[\"100.0\",\"0.5\"],[\"-1\",2]]],\"objects\":{\"line\":{\"arcs\":[\"0\"],\"type\":\"LineString\"}},\"type\":\"Topology\"}";
+        assert!(topo_json_str.parse::<TopoJson>().is_err());
+
+        let options = ParseOptions {
+            numeric_strings: true,
+            ..Default::default()
+        };
+        let topo = TopoJson::from_str_with_options(topo_json_str, &options)
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(topo.arcs, vec![vec![vec![100.0, 0.5], vec![-1.0, 2.0]]]);
+        assert_eq!(topo.objects[0].geometry.value, Value::LineString(vec![0]));
+
+        // Strings which aren't numbers still fail:
+        for not_numeric in ["x", "NaN", "inf", "-infinity"] {
+            assert_eq!(
+                TopoJson::from_str_with_options(
+                    &topo_json_str.replace("0.5", not_numeric),
+                    &options
+                )
+                .err(),
+                Some(Error::ExpectedF64Value)
+            );
+        }
+        assert_eq!(
+            TopoJson::from_str_with_options(
+                &topo_json_str.replace("[\"0\"]", "[\"0.5\"]"),
+                &options
+            )
+            .err(),
+            Some(Error::At {
+                path: String::from("object 'line'"),
+                source: Box::new(Error::Expectedi32Value)
+            })
+        );
+
+        // The values of the transform may also be strings:
+        let topo_json_str = topo_json_str.replace(
+            "\"type\":\"Topology\"",
+            "\"transform\":{\"scale\":[\"0.5\",1],\"translate\":[\"10\",\"20\"]},\"type\":\"Topology\"",
+        );
+        let topo = TopoJson::from_str_with_options(&topo_json_str, &options)
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(
+            topo.transform,
+            Some(TransformParams {
+                scale: [0.5, 1.0],
+                translate: [10.0, 20.0]
+            })
+        );
+    }
+
+    #[test]
+    fn arc_key_pairs_reversed_arcs() {
+        let arc = vec![vec![0.0, 0.0], vec![1.0, 2.0], vec![3.0, 1.0]];
+        let mut reversed = arc.clone();
+        reversed.reverse();
+        let other = vec![vec![0.0, 0.0], vec![1.0, 2.0]];
+
+        let mut index = HashMap::new();
+        index.insert(arc_key(&arc), 0);
+        index.insert(arc_key(&other), 1);
+
+        assert_eq!(index.get(&arc_key(&arc)), Some(&0));
+        assert_eq!(index.get(&arc_key_reversed(&reversed)), Some(&0));
+        assert_ne!(arc_key(&arc), arc_key(&reversed));
+        assert_eq!(arc_key(&reversed), arc_key_reversed(&arc));
+    }
+
+    #[test]
+    fn arc_key_is_bit_exact() {
+        let arc = vec![vec![0.0, 0.0], vec![1.0, 1.0]];
+        let negative_zero = vec![vec![-0.0, 0.0], vec![1.0, 1.0]];
+        let nudged = vec![vec![0.0, 0.0], vec![1.0 + f64::EPSILON, 1.0]];
+        assert_ne!(arc_key(&arc), arc_key(&negative_zero));
+        assert_ne!(arc_key(&arc), arc_key(&nudged));
+    }
+
+    #[test]
+    fn check_finite_values() {
+        assert_eq!(
+            check_finite(&[0.5, -1.0], Error::TransformNotFinite),
+            Ok(())
+        );
+        assert_eq!(
+            check_finite(&[f64::NAN, 0.5], Error::TransformNotFinite),
+            Err(Error::TransformNotFinite)
+        );
+        assert_eq!(
+            check_finite(&[0.5, f64::INFINITY], Error::TransformNotFinite),
+            Err(Error::TransformNotFinite)
+        );
+        assert_eq!(
+            check_finite(&[f64::NEG_INFINITY], Error::BboxNotFinite),
+            Err(Error::BboxNotFinite)
+        );
+    }
+
+    #[test]
+    fn validate_positions_too_short() {
+        let topo = "{\"arcs\":[[[0,0],[1]]],\"objects\":{\"example\":{\"arcs\":[0],\"type\":\"LineString\"}},\"type\":\"Topology\"}"
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(
+            validate_positions(&topo),
+            Err(Error::PositionTooShort { found: 1 })
+        );
+
+        let topo = "{\"arcs\":[[[0,0],[1,1]]],\"objects\":{\"example\":{\"coordinates\":[],\"type\":\"Point\"}},\"type\":\"Topology\"}"
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(
+            validate_positions(&topo),
+            Err(Error::PositionTooShort { found: 0 })
+        );
+        assert_eq!(topo.validate(), validate_positions(&topo));
+    }
+
+    #[test]
+    fn validate_reserved_foreign_member() {
+        let mut topo = "{\"arcs\":[],\"objects\":{\"example\":{\"coordinates\":[0,0],\"type\":\"Point\"}},\"type\":\"Topology\"}"
+            .parse::<TopoJson>()
+            .unwrap()
+            .try_unwrap_topology()
+            .unwrap();
+        assert_eq!(validate_foreign_members(&topo), Ok(()));
+
+        // Only the members of its type are reserved for a geometry:
+        let mut foreign_members = JsonObject::new();
+        foreign_members.insert(String::from("arcs"), json!([0]));
+        topo.objects[0].geometry.foreign_members = Some(foreign_members);
+        assert_eq!(validate_foreign_members(&topo), Ok(()));
+
+        let mut foreign_members = JsonObject::new();
+        foreign_members.insert(String::from("type"), json!("Polygon"));
+        foreign_members.insert(String::from("other"), json!(1));
+        topo.objects[0].geometry.foreign_members = Some(foreign_members);
+        assert_eq!(
+            topo.validate(),
+            Err(Error::ReservedForeignMemberKey(String::from("type")))
+        );
+
+        // The foreign member doesn't replace the actual type when serializing:
+        assert_eq!(
+            serde_json::to_string(&topo).unwrap(),
+            "{\"arcs\":[],\"objects\":{\"example\":{\"coordinates\":[0.0,0.0],\"other\":1,\"type\":\"Point\"}},\"type\":\"Topology\"}"
+        );
+    }
+}